@@ -0,0 +1,82 @@
+//! A tournament runner for a toy game, where both agents pick a number and the higher one wins.
+//!
+//! ```sh
+//! cargo run --bin runner --features runner -- examples/runner.toml
+//! ```
+
+use rand::Rng;
+use tournament_rs::error::Error;
+use tournament_rs::game::{Match, MatchResult, Outcome};
+use tournament_rs::player::{Player, PlayerGen};
+use tournament_rs::runner::{run_main, AgentRegistry};
+
+enum Strategy {
+    Random,
+    Fixed(u32),
+}
+
+impl Strategy {
+    fn pick(&self) -> u32 {
+        match self {
+            Strategy::Random => rand::thread_rng().gen_range(0..=10),
+            Strategy::Fixed(n) => *n,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HighCardResult(u32, u32);
+
+impl MatchResult for HighCardResult {
+    fn outcome(&self) -> Outcome {
+        match self.0.cmp(&self.1) {
+            std::cmp::Ordering::Greater => Outcome::WinP1,
+            std::cmp::Ordering::Less => Outcome::WinP2,
+            std::cmp::Ordering::Equal => Outcome::Draw,
+        }
+    }
+}
+
+struct HighCard {
+    player1: Strategy,
+    player2: Strategy,
+}
+
+impl Match for HighCard {
+    type Agent = Strategy;
+    type MatchResult = HighCardResult;
+
+    fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+        HighCard {
+            player1: player1.unpack(),
+            player2: player2.unpack(),
+        }
+    }
+
+    fn playout(&mut self) -> anyhow::Result<Self::MatchResult> {
+        Ok(HighCardResult(self.player1.pick(), self.player2.pick()))
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mut registry = AgentRegistry::<HighCard>::new();
+    registry
+        .register(
+            "random",
+            PlayerGen::new(|| Strategy::Random).with_name("random"),
+        )
+        .register(
+            "low",
+            PlayerGen::new(|| Strategy::Fixed(3)).with_name("low"),
+        )
+        .register(
+            "mid",
+            PlayerGen::new(|| Strategy::Fixed(5)).with_name("mid"),
+        )
+        .register(
+            "high",
+            PlayerGen::new(|| Strategy::Fixed(7)).with_name("high"),
+        );
+
+    run_main(&registry)
+}
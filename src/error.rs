@@ -0,0 +1,270 @@
+//! The error type of the crate.
+
+use crate::game::{Fault, FaultKind, Side};
+use crate::player::PlayerId;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors that can be wrapped as the source of a match failure.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A scheduler was configured with invalid parameters.
+    Scheduler(String),
+    /// A tournament run was configured with invalid parameters.
+    Config(String),
+    /// A scheduler cannot provide a pairing without blocking.
+    WouldBlock,
+    /// No player with the given id exists.
+    PlayerNotFound(PlayerId),
+    /// Another player already uses the given key.
+    DuplicateKey(String),
+    /// A match could not be played. `side` is the player at fault, if the failure can be
+    /// attributed to one of them, and `kind` the kind of the fault.
+    Match {
+        side: Option<Side>,
+        kind: FaultKind,
+        source: BoxError,
+    },
+    /// A player did not respond within its time limit.
+    Timeout { side: Option<Side>, limit: Duration },
+    /// A numerical computation failed, e.g. a rating fit.
+    Numerical(String),
+    /// Reading or writing failed.
+    Io(std::io::Error),
+    /// A value could not be serialized or deserialized, e.g. a malformed config file or
+    /// message of a worker.
+    Serialization(BoxError),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    /// A match failure that can not be attributed to either player.
+    pub fn match_failed(source: impl Into<BoxError>) -> Self {
+        Error::Match {
+            side: None,
+            kind: FaultKind::Other,
+            source: source.into(),
+        }
+    }
+
+    /// A match failure caused by the player on `side`.
+    pub fn at_fault(side: Side, source: impl Into<BoxError>) -> Self {
+        Error::Match {
+            side: Some(side),
+            kind: FaultKind::Other,
+            source: source.into(),
+        }
+    }
+
+    /// The player on `side` made an illegal move.
+    pub fn illegal_move(side: Side, source: impl Into<BoxError>) -> Self {
+        Error::Match {
+            side: Some(side),
+            kind: FaultKind::IllegalMove,
+            source: source.into(),
+        }
+    }
+
+    /// The agent of the player on `side` crashed.
+    pub fn crashed(side: Side, source: impl Into<BoxError>) -> Self {
+        Error::Match {
+            side: Some(side),
+            kind: FaultKind::Crash,
+            source: source.into(),
+        }
+    }
+
+    /// Attributes a match failure or timeout to the player on `side`.
+    /// Other errors are returned unchanged.
+    pub fn with_side(self, side: Side) -> Self {
+        match self {
+            Error::Match { kind, source, .. } => Error::Match {
+                side: Some(side),
+                kind,
+                source,
+            },
+            Error::Timeout { limit, .. } => Error::Timeout {
+                side: Some(side),
+                limit,
+            },
+            error => error,
+        }
+    }
+
+    /// The side at fault, if the error is attributed to one of the players.
+    pub fn side(&self) -> Option<Side> {
+        match self {
+            Error::Match { side, .. } | Error::Timeout { side, .. } => *side,
+            _ => None,
+        }
+    }
+
+    /// The fault of a player, if the error is attributed to one of them.
+    pub fn fault(&self) -> Option<Fault> {
+        let kind = match self {
+            Error::Match { kind, .. } => *kind,
+            Error::Timeout { .. } => FaultKind::Timeout,
+            _ => return None,
+        };
+        self.side().map(|side| Fault { side, kind })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let side = |side: &Option<Side>| match side {
+            Some(Side::Player1) => " (player 1 at fault)",
+            Some(Side::Player2) => " (player 2 at fault)",
+            None => "",
+        };
+
+        match self {
+            Error::Scheduler(msg) => write!(f, "invalid scheduler configuration: {}", msg),
+            Error::Config(msg) => write!(f, "invalid configuration: {}", msg),
+            Error::WouldBlock => write!(f, "scheduler would block"),
+            Error::PlayerNotFound(id) => write!(f, "Player with id {} not found", id),
+            Error::DuplicateKey(key) => write!(f, "Player with key {} already exists", key),
+            Error::Match {
+                side: s, source, ..
+            } => write!(f, "match failed{}: {}", side(s), source),
+            Error::Timeout { side: s, limit } => {
+                write!(f, "no response within {:?}{}", limit, side(s))
+            }
+            Error::Numerical(msg) => write!(f, "numerical failure: {}", msg),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Serialization(e) => write!(f, "serialization failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Match { source, .. } => Some(source.as_ref()),
+            Error::Io(e) => Some(e),
+            Error::Serialization(source) => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Errors of `Match::playout`. An `Error` of the crate is unwrapped, to keep its attribution
+/// to a player, everything else is a match failure.
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<Error>() {
+            Ok(error) => error,
+            Err(error) => Error::match_failed(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            Error::Io(error.into())
+        } else {
+            Error::Serialization(error.into())
+        }
+    }
+}
+
+#[cfg(feature = "runner")]
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Error::Serialization(error.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_anyhow() {
+        let error = Error::from(anyhow::anyhow!("board exploded"));
+        assert!(matches!(error, Error::Match { side: None, .. }));
+        assert_eq!(error.to_string(), "match failed: board exploded");
+        assert!(error.fault().is_none());
+
+        // errors of the crate keep their attribution
+        let error = Error::from(anyhow::Error::from(Error::illegal_move(
+            Side::Player2,
+            "e9 is not a square",
+        )));
+        assert_eq!(
+            error.fault(),
+            Some(Fault {
+                side: Side::Player2,
+                kind: FaultKind::IllegalMove
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "match failed (player 2 at fault): e9 is not a square"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let timeout = Error::Timeout {
+            side: None,
+            limit: Duration::from_millis(1500),
+        };
+        assert_eq!(timeout.to_string(), "no response within 1.5s");
+        assert_eq!(
+            timeout.with_side(Side::Player1).to_string(),
+            "no response within 1.5s (player 1 at fault)"
+        );
+        assert_eq!(
+            Error::PlayerNotFound(3).to_string(),
+            "Player with id 3 not found"
+        );
+        assert_eq!(
+            Error::Config("no players".into()).to_string(),
+            "invalid configuration: no players"
+        );
+        assert!(Error::WouldBlock.with_side(Side::Player1).side().is_none());
+    }
+
+    #[test]
+    fn test_from_io() {
+        let error = Error::from(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        assert!(matches!(error, Error::Io(_)));
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_serde_json() {
+        let error = serde_json::from_str::<u32>("{").unwrap_err();
+        assert!(matches!(Error::from(error), Error::Serialization(_)));
+
+        let error = serde_json::to_writer(FailingWriter, &1).unwrap_err();
+        assert!(matches!(Error::from(error), Error::Io(_)));
+    }
+
+    #[cfg(feature = "serde")]
+    struct FailingWriter;
+
+    #[cfg(feature = "serde")]
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
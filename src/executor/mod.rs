@@ -0,0 +1,417 @@
+//! Executors run the matches of a tournament, either locally or on remote workers.
+//!
+//! The tournament hands each match to its executor as a `Job`, which names the pairing and
+//! the seed of the match, and receives a `Completed` match in return.
+//!
+//! Besides the executors of this module, matches can be run on any pool or runtime that
+//! can spawn a closure, using a `SpawnExecutor`:
+//!
+//! ```ignore
+//! // on the global rayon pool
+//! let executor = SpawnExecutor::new(8, |task| rayon::spawn(task));
+//! // on the blocking threads of a tokio runtime
+//! let handle = tokio::runtime::Handle::current();
+//! let executor = SpawnExecutor::new(8, move |task| drop(handle.spawn_blocking(task)));
+//!
+//! let tournament = Tournament::new(scheduler, scoring, players).with_executor(executor);
+//! ```
+
+#[cfg(feature = "distributed")]
+mod tcp;
+
+#[cfg(feature = "distributed")]
+pub use tcp::*;
+
+use crate::error::Error;
+#[cfg(feature = "async")]
+use crate::game::AsyncMatch;
+use crate::game::{LabelledOutcome, Match};
+use crate::player::{Feedback, PlayerData, PlayerGen, PlayerId, PlayerRepository};
+use crate::prelude::MatchResult;
+use crate::tournaments::MatchOutput;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use threadpool::ThreadPool;
+
+/// A match that is ready to be played.
+pub struct Job<M: Match> {
+    pub id: usize,
+    pub seed: u64,
+    player1: (PlayerData, PlayerGen<M>),
+    player2: (PlayerData, PlayerGen<M>),
+}
+
+impl<M: Match> Job<M> {
+    /// Creates the job of match `id` between two players of the repository.
+    pub fn new(
+        id: usize,
+        seed: u64,
+        players: &PlayerRepository<M>,
+        player1: PlayerId,
+        player2: PlayerId,
+    ) -> Result<Self, Error> {
+        let gen1 = players.get_raw(player1)?;
+        let gen2 = players.get_raw(player2)?;
+        Ok(Job {
+            id,
+            seed,
+            player1: (gen1.data(player1), gen1),
+            player2: (gen2.data(player2), gen2),
+        })
+    }
+
+    pub fn player1(&self) -> &PlayerData {
+        &self.player1.0
+    }
+
+    pub fn player2(&self) -> &PlayerData {
+        &self.player2.0
+    }
+
+    /// Plays the match on the current thread.
+    ///
+    /// Feedback hooks of the players are called, and pooled agents are returned to their pool,
+    /// before the match is completed.
+    pub fn run(self) -> Completed<M> {
+        let start = Instant::now();
+        let mut match_ = self.new_match();
+        let result = match_.playout().map_err(Error::from);
+        self.finish(match_, result, start.elapsed())
+    }
+
+    /// Plays the match of an `AsyncMatch`. See `run`.
+    #[cfg(feature = "async")]
+    pub async fn run_async(self) -> Completed<M>
+    where
+        M: AsyncMatch,
+    {
+        let start = Instant::now();
+        let mut match_ = self.new_match();
+        let result = match_.playout_async().await.map_err(Error::from);
+        self.finish(match_, result, start.elapsed())
+    }
+
+    /// Creates the agents of both players, and sets up the match.
+    fn new_match(&self) -> M {
+        let mut match_ = M::new(
+            self.player1.1.instantiate(self.player1.0.id()),
+            self.player2.1.instantiate(self.player2.0.id()),
+        );
+        match_.set_seed(self.seed);
+        match_
+    }
+
+    /// Delivers feedback, returns the agents and labels the result of a played match.
+    fn finish(
+        self,
+        match_: M,
+        result: Result<M::MatchResult, Error>,
+        duration: Duration,
+    ) -> Completed<M> {
+        let (p1_d, source1) = &self.player1;
+        let (p2_d, source2) = &self.player2;
+
+        let output = result.map(|result| {
+            let outcome = LabelledOutcome::new(result.outcome(), p1_d.clone(), p2_d.clone());
+            (outcome, result)
+        });
+
+        let recover = source1.recover_agents().or(source2.recover_agents());
+        let (mut a1, mut a2) = match recover {
+            Some(recover) => recover(match_),
+            None => (None, None),
+        };
+
+        if let Ok((outcome, result)) = &output {
+            let feedback = |player, opponent| Feedback {
+                player,
+                opponent,
+                outcome,
+                result,
+            };
+            source1.feedback(a1.as_mut(), &feedback(p1_d, p2_d));
+            source2.feedback(a2.as_mut(), &feedback(p2_d, p1_d));
+        }
+
+        for (source, agent) in [(source1, a1), (source2, a2)] {
+            if let Some(agent) = agent {
+                source.return_agent(agent);
+            }
+        }
+
+        Completed {
+            id: self.id,
+            player1: self.player1.0,
+            player2: self.player2.0,
+            output,
+            duration,
+        }
+    }
+
+    /// Completes the job with a result that has been produced elsewhere, e.g. by a remote
+    /// worker.
+    pub fn complete(
+        self,
+        result: Result<M::MatchResult, Error>,
+        duration: Duration,
+    ) -> Completed<M> {
+        let (player1, player2) = (self.player1.0, self.player2.0);
+        let output = result.map(|result| {
+            let outcome = LabelledOutcome::new(result.outcome(), player1.clone(), player2.clone());
+            (outcome, result)
+        });
+
+        Completed {
+            id: self.id,
+            player1,
+            player2,
+            output,
+            duration,
+        }
+    }
+}
+
+/// A match that has been played by an executor.
+pub struct Completed<M: Match> {
+    pub id: usize,
+    pub player1: PlayerData,
+    pub player2: PlayerData,
+    pub output: MatchOutput<M>,
+    pub duration: Duration,
+}
+
+/// Runs the jobs of a tournament.
+pub trait Executor<M: Match> {
+    /// The number of jobs that may be submitted before a completed job is received.
+    fn capacity(&self) -> usize;
+
+    /// Submits a job for execution.
+    fn submit(&mut self, job: Job<M>);
+
+    /// Blocks until one of the submitted jobs has completed.
+    /// Jobs may complete in any order.
+    fn recv(&mut self) -> Completed<M>;
+}
+
+impl<M: Match, E: Executor<M> + ?Sized> Executor<M> for Box<E> {
+    fn capacity(&self) -> usize {
+        (**self).capacity()
+    }
+
+    fn submit(&mut self, job: Job<M>) {
+        (**self).submit(job)
+    }
+
+    fn recv(&mut self) -> Completed<M> {
+        (**self).recv()
+    }
+}
+
+/// Runs jobs on a local thread pool. This is the default executor of a tournament.
+pub struct ThreadPoolExecutor<M: Match> {
+    pool: ThreadPool,
+    channel: (mpsc::Sender<Completed<M>>, mpsc::Receiver<Completed<M>>),
+}
+
+impl<M: Match> ThreadPoolExecutor<M> {
+    pub fn new(threads: usize) -> Self {
+        ThreadPoolExecutor {
+            pool: ThreadPool::new(threads),
+            channel: mpsc::channel(),
+        }
+    }
+
+    pub fn set_threads(&mut self, count: usize) {
+        self.pool.set_num_threads(count);
+    }
+}
+
+impl<M: Match + 'static> Executor<M> for ThreadPoolExecutor<M> {
+    fn capacity(&self) -> usize {
+        self.pool.max_count()
+    }
+
+    fn submit(&mut self, job: Job<M>) {
+        let tx = self.channel.0.clone();
+        self.pool.execute(move || {
+            tx.send(job.run()).expect("Error sending match result");
+        });
+    }
+
+    fn recv(&mut self) -> Completed<M> {
+        self.channel.1.recv().expect("Error receiving match result")
+    }
+}
+
+/// Plays each job on the calling thread, when its result is received.
+///
+/// Matches are played one at a time and in the order they are scheduled, which makes a seeded
+/// tournament fully reproducible, and lets a debugger step through the matches.
+pub struct InlineExecutor<M: Match> {
+    jobs: VecDeque<Job<M>>,
+}
+
+impl<M: Match> Default for InlineExecutor<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Match> InlineExecutor<M> {
+    pub fn new() -> Self {
+        InlineExecutor {
+            jobs: VecDeque::new(),
+        }
+    }
+}
+
+impl<M: Match> Executor<M> for InlineExecutor<M> {
+    fn capacity(&self) -> usize {
+        1
+    }
+
+    fn submit(&mut self, job: Job<M>) {
+        self.jobs.push_back(job);
+    }
+
+    fn recv(&mut self) -> Completed<M> {
+        self.jobs
+            .pop_front()
+            .expect("No job has been submitted")
+            .run()
+    }
+}
+
+/// A job, packaged to run on an arbitrary thread.
+pub type Task = Box<dyn FnOnce() + Send>;
+
+/// Runs jobs with a function that spawns tasks, e.g. on a custom thread pool or runtime.
+pub struct SpawnExecutor<M: Match, F> {
+    spawn: F,
+    capacity: usize,
+    channel: (mpsc::Sender<Completed<M>>, mpsc::Receiver<Completed<M>>),
+}
+
+impl<M: Match, F: FnMut(Task)> SpawnExecutor<M, F> {
+    /// Creates an executor that passes every job to `spawn`, with at most `capacity` jobs
+    /// running at once.
+    pub fn new(capacity: usize, spawn: F) -> Self {
+        SpawnExecutor {
+            spawn,
+            capacity: capacity.max(1),
+            channel: mpsc::channel(),
+        }
+    }
+}
+
+impl<M: Match + 'static, F: FnMut(Task)> Executor<M> for SpawnExecutor<M, F> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn submit(&mut self, job: Job<M>) {
+        let tx = self.channel.0.clone();
+        (self.spawn)(Box::new(move || {
+            // the executor may have been dropped in the meantime
+            let _ = tx.send(job.run());
+        }));
+    }
+
+    fn recv(&mut self) -> Completed<M> {
+        self.channel.1.recv().expect("Error receiving match result")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Outcome;
+    use crate::player::{Player, PlayerGen};
+    use crate::scheduling::RoundRobbinScheduler;
+    use crate::scoring::Standings;
+    use crate::tournaments::Tournament;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// A match decided by a coin flip of the seeded match.
+    struct Coin(u64);
+
+    #[derive(Debug)]
+    struct CoinResult(Outcome);
+
+    impl MatchResult for CoinResult {
+        fn outcome(&self) -> Outcome {
+            self.0.clone()
+        }
+    }
+
+    impl Match for Coin {
+        type Agent = ();
+        type MatchResult = CoinResult;
+
+        fn new(_: Player<Self>, _: Player<Self>) -> Self {
+            Coin(0)
+        }
+
+        fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+            let heads = StdRng::seed_from_u64(self.0).gen_bool(0.5);
+            Ok(CoinResult(if heads {
+                Outcome::WinP1
+            } else {
+                Outcome::WinP2
+            }))
+        }
+
+        fn set_seed(&mut self, seed: u64) {
+            self.0 = seed;
+        }
+    }
+
+    fn tournament(seed: u64) -> Tournament<Coin, RoundRobbinScheduler, Standings> {
+        let mut players = PlayerRepository::new();
+        for _ in 0..6 {
+            players.add_player(PlayerGen::new(|| ()));
+        }
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+        tournament.set_seed(seed);
+        tournament
+    }
+
+    fn winners(outputs: impl Iterator<Item = MatchOutput<Coin>>) -> Vec<PlayerId> {
+        outputs
+            .map(|output| output.unwrap().0.players().0.id())
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_is_reproducible() {
+        let play = || {
+            let mut tournament = tournament(7).with_executor(InlineExecutor::new());
+            tournament.init();
+            winners(tournament)
+        };
+
+        let first = play();
+        assert_eq!(first.len(), 15);
+        assert_eq!(first, play());
+    }
+
+    #[test]
+    fn test_spawn_executor() {
+        let executor = SpawnExecutor::new(4, |task| drop(std::thread::spawn(task)));
+        let mut spawned = tournament(7).with_executor(executor);
+        spawned.init();
+
+        let mut spawned = winners(spawned);
+        let mut inline = {
+            let mut tournament = tournament(7).with_executor(InlineExecutor::new());
+            tournament.init();
+            winners(tournament)
+        };
+        spawned.sort_unstable();
+        inline.sort_unstable();
+        assert_eq!(spawned, inline);
+    }
+}
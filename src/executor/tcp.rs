@@ -0,0 +1,526 @@
+//! Distributes jobs to worker processes over TCP.
+//!
+//! Workers connect to the `TcpExecutor`, and exchange JSON messages with it, one per line.
+//! For every job, the executor sends the match id, the ids of both players and the seed, and
+//! the worker replies with the result of the match. A worker runs one job per connection at a
+//! time, and opens several connections to run jobs in parallel.
+//!
+//! Workers create the agents themselves, and therefore need a `PlayerRepository` with the same
+//! players under the same ids as the tournament, i.e. the players must be added in the same
+//! order. If a worker disconnects before it has replied, or does not reply within the worker
+//! timeout, its job is handed to another worker.
+
+use super::{Completed, Executor, Job};
+use crate::error::Error;
+use crate::game::Match;
+use crate::game::{FaultKind, Side};
+use crate::player::{PlayerId, PlayerRepository};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::ffi::OsStr;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JobMessage {
+    id: usize,
+    player1: PlayerId,
+    player2: PlayerId,
+    seed: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ResultMessage<R> {
+    id: usize,
+    result: Result<R, RemoteError>,
+    duration: Duration,
+}
+
+/// A failed match, as reported by a worker. Keeps the fault of the error, and the time limit
+/// of a timeout.
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoteError {
+    side: Option<Side>,
+    kind: FaultKind,
+    limit: Option<Duration>,
+    message: String,
+}
+
+impl From<Error> for RemoteError {
+    fn from(error: Error) -> Self {
+        let (kind, limit) = match &error {
+            Error::Match { kind, .. } => (*kind, None),
+            Error::Timeout { limit, .. } => (FaultKind::Timeout, Some(*limit)),
+            _ => (FaultKind::Other, None),
+        };
+        RemoteError {
+            side: error.side(),
+            kind,
+            limit,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<RemoteError> for Error {
+    fn from(error: RemoteError) -> Self {
+        match error.limit {
+            Some(limit) => Error::Timeout {
+                side: error.side,
+                limit,
+            },
+            None => Error::Match {
+                side: error.side,
+                kind: error.kind,
+                source: error.message.into(),
+            },
+        }
+    }
+}
+
+/// The state shared between the executor and the threads serving its workers.
+struct Shared<M: Match> {
+    queue: Mutex<VecDeque<Job<M>>>,
+    available: Condvar,
+    closed: AtomicBool,
+    workers: AtomicUsize,
+    /// How long to wait for the result of a job, before the worker is considered lost.
+    timeout: Mutex<Option<Duration>>,
+}
+
+impl<M: Match> Shared<M> {
+    /// Blocks until a job is queued, or returns `None` once the executor is dropped.
+    fn next_job(&self) -> Option<Job<M>> {
+        let mut queue = self.queue.lock().unwrap();
+        loop {
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+            if let Some(job) = queue.pop_front() {
+                return Some(job);
+            }
+            queue = self.available.wait(queue).unwrap();
+        }
+    }
+
+    fn push(&self, job: Job<M>) {
+        self.queue.lock().unwrap().push_back(job);
+        self.available.notify_one();
+    }
+
+    /// Puts the job of a lost worker back at the front of the queue.
+    fn requeue(&self, job: Job<M>) {
+        self.queue.lock().unwrap().push_front(job);
+        self.available.notify_one();
+    }
+}
+
+/// Runs jobs on workers that connect over TCP, on this or other machines.
+///
+/// Submitted jobs are queued until a worker is available. If no worker ever connects,
+/// `recv` blocks forever.
+pub struct TcpExecutor<M: Match> {
+    address: SocketAddr,
+    shared: Arc<Shared<M>>,
+    results: mpsc::Receiver<Completed<M>>,
+    capacity: usize,
+    children: Vec<Child>,
+}
+
+impl<M> TcpExecutor<M>
+where
+    M: Match + 'static,
+    M::MatchResult: Serialize + DeserializeOwned,
+{
+    /// Listens for workers on the given address.
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, Error> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        listener.set_nonblocking(true)?;
+
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+            closed: AtomicBool::new(false),
+            workers: AtomicUsize::new(0),
+            timeout: Mutex::new(None),
+        });
+        let (tx, results) = mpsc::channel();
+
+        let accepting = shared.clone();
+        thread::spawn(move || {
+            // the listener is polled, so that the thread stops once the executor is dropped
+            while !accepting.closed.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let shared = accepting.clone();
+                        let tx = tx.clone();
+                        thread::spawn(move || serve_worker(stream, shared, tx));
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(10))
+                    }
+                    Err(_) => thread::sleep(Duration::from_millis(100)),
+                }
+            }
+        });
+
+        Ok(TcpExecutor {
+            address,
+            shared,
+            results,
+            capacity: 32,
+            children: Vec::new(),
+        })
+    }
+
+    /// Sets the number of jobs that are handed out before a result is received.
+    /// Should be at least the total number of worker connections. The default is 32.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sets how long a worker may take to reply to a job. If it does not reply in time, the
+    /// connection is closed and the job is handed to another worker, so the timeout should be
+    /// well above the longest expected match. By default, the executor waits indefinitely.
+    pub fn with_worker_timeout(self, timeout: Duration) -> Self {
+        // sockets reject a zero timeout
+        let timeout = timeout.max(Duration::from_millis(1));
+        *self.shared.timeout.lock().unwrap() = Some(timeout);
+        self
+    }
+
+    /// The address workers connect to.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The number of currently connected worker connections.
+    pub fn workers(&self) -> usize {
+        self.shared.workers.load(Ordering::SeqCst)
+    }
+
+    /// Starts `count` local worker processes, passing the address of the executor as the last
+    /// argument. The program usually runs a `Worker`. Workers are killed when the executor is
+    /// dropped.
+    pub fn spawn_local_workers<I, S>(
+        &mut self,
+        program: impl AsRef<OsStr>,
+        args: I,
+        count: usize,
+    ) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S> + Clone,
+        S: AsRef<OsStr>,
+    {
+        for _ in 0..count {
+            let child = Command::new(program.as_ref())
+                .args(args.clone())
+                .arg(self.address.to_string())
+                .spawn()?;
+            self.children.push(child);
+        }
+        Ok(())
+    }
+}
+
+impl<M: Match> Drop for TcpExecutor<M> {
+    fn drop(&mut self) {
+        {
+            let _queue = self.shared.queue.lock().unwrap();
+            self.shared.closed.store(true, Ordering::SeqCst);
+        }
+        self.shared.available.notify_all();
+
+        for child in &mut self.children {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl<M> Executor<M> for TcpExecutor<M>
+where
+    M: Match + 'static,
+    M::MatchResult: Serialize + DeserializeOwned,
+{
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn submit(&mut self, job: Job<M>) {
+        self.shared.push(job);
+    }
+
+    fn recv(&mut self) -> Completed<M> {
+        self.results.recv().expect("Error receiving match result")
+    }
+}
+
+/// Hands jobs to a single worker connection, until the executor is dropped or the worker is
+/// lost.
+fn serve_worker<M>(stream: TcpStream, shared: Arc<Shared<M>>, tx: mpsc::Sender<Completed<M>>)
+where
+    M: Match,
+    M::MatchResult: Serialize + DeserializeOwned,
+{
+    let _ = stream.set_nonblocking(false);
+    let Ok(mut reader) = stream.try_clone().map(BufReader::new) else {
+        return;
+    };
+    let mut writer = stream;
+
+    shared.workers.fetch_add(1, Ordering::SeqCst);
+    while let Some(job) = shared.next_job() {
+        let timeout = *shared.timeout.lock().unwrap();
+        if writer.set_read_timeout(timeout).is_err() {
+            shared.requeue(job);
+            break;
+        }
+
+        match exchange(&mut writer, &mut reader, &job) {
+            Ok(message) => {
+                let result = message.result.map_err(Error::from);
+                if tx.send(job.complete(result, message.duration)).is_err() {
+                    break;
+                }
+            }
+            Err(_) => {
+                shared.requeue(job);
+                break;
+            }
+        }
+    }
+    shared.workers.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Sends a job to a worker, and waits for its result.
+fn exchange<M>(
+    writer: &mut TcpStream,
+    reader: &mut impl BufRead,
+    job: &Job<M>,
+) -> io::Result<ResultMessage<M::MatchResult>>
+where
+    M: Match,
+    M::MatchResult: DeserializeOwned,
+{
+    let message = JobMessage {
+        id: job.id,
+        player1: job.player1().id(),
+        player2: job.player2().id(),
+        seed: job.seed,
+    };
+    writeln!(writer, "{}", serde_json::to_string(&message)?)?;
+    writer.flush()?;
+
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+
+    let result: ResultMessage<M::MatchResult> = serde_json::from_str(&line)?;
+    if result.id != job.id {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Worker replied to match {} instead of {}",
+                result.id, job.id
+            ),
+        ));
+    }
+    Ok(result)
+}
+
+/// Plays the jobs of a `TcpExecutor`.
+///
+/// ```ignore
+/// fn main() -> anyhow::Result<()> {
+///     let address = std::env::args().last().unwrap();
+///     Worker::new(my_players()).with_threads(4).connect(address)
+/// }
+/// ```
+pub struct Worker<M: Match> {
+    players: PlayerRepository<M>,
+    threads: usize,
+}
+
+impl<M> Worker<M>
+where
+    M: Match + 'static,
+    M::MatchResult: Serialize + DeserializeOwned,
+{
+    /// Creates a worker for the players of the tournament, which must have the same ids as in
+    /// the tournament.
+    pub fn new(players: PlayerRepository<M>) -> Self {
+        Worker {
+            players,
+            threads: 1,
+        }
+    }
+
+    /// Sets the number of jobs that are played in parallel. The default is 1.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Connects to the executor and plays jobs until the executor closes the connections.
+    pub fn connect(&self, address: impl ToSocketAddrs) -> Result<(), Error> {
+        let address = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::from(ErrorKind::AddrNotAvailable))?;
+
+        thread::scope(|scope| {
+            let handles = (0..self.threads)
+                .map(|_| scope.spawn(|| self.serve(TcpStream::connect(address)?)))
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .try_for_each(|handle| handle.join().expect("Worker thread panicked"))
+        })
+    }
+
+    /// Plays the jobs received over one connection.
+    fn serve(&self, stream: TcpStream) -> Result<(), Error> {
+        let reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+
+        for line in reader.lines() {
+            let message: JobMessage = serde_json::from_str(&line?)?;
+
+            let reply = match Job::new(
+                message.id,
+                message.seed,
+                &self.players,
+                message.player1,
+                message.player2,
+            ) {
+                Ok(job) => {
+                    let completed = job.run();
+                    ResultMessage {
+                        id: message.id,
+                        result: completed
+                            .output
+                            .map(|(_, result)| result)
+                            .map_err(RemoteError::from),
+                        duration: completed.duration,
+                    }
+                }
+                Err(e) => ResultMessage {
+                    id: message.id,
+                    result: Err(e.into()),
+                    duration: Duration::ZERO,
+                },
+            };
+
+            writeln!(writer, "{}", serde_json::to_string(&reply)?)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduling::RoundRobbinScheduler;
+    use crate::scoring::Standings;
+    use crate::test_util::{by_strength, Strength};
+    use crate::tournaments::Tournament;
+
+    fn players() -> PlayerRepository<Strength> {
+        by_strength(&[0, 1, 2, 3, 4])
+    }
+
+    fn play(executor: TcpExecutor<Strength>) -> Standings {
+        let players = players();
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament =
+            Tournament::new(scheduler, Standings::new(), players).with_executor(executor);
+        tournament.init();
+
+        for result in tournament.by_ref() {
+            result.unwrap();
+        }
+        tournament.into_scoring()
+    }
+
+    #[test]
+    fn test_remote_workers() {
+        let executor = TcpExecutor::bind("127.0.0.1:0").unwrap();
+        let address = executor.address();
+        let worker = thread::spawn(move || Worker::new(players()).with_threads(2).connect(address));
+
+        let standings = play(executor);
+        for (rank, record) in standings.records().iter().enumerate() {
+            assert_eq!(record.player.id(), 4 - rank);
+            assert_eq!(record.wins as usize, 4 - rank);
+        }
+        worker.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_lost_worker() {
+        let executor = TcpExecutor::bind("127.0.0.1:0").unwrap();
+        let address = executor.address();
+
+        // a worker that disconnects after receiving its first job
+        let (lost, took_job) = mpsc::channel();
+        thread::spawn(move || {
+            let stream = TcpStream::connect(address).unwrap();
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            lost.send(()).unwrap();
+        });
+        let worker = thread::spawn(move || {
+            took_job.recv().unwrap();
+            Worker::new(players()).connect(address)
+        });
+
+        let standings = play(executor);
+        let games: u32 = standings.records().iter().map(|r| r.games()).sum();
+        assert_eq!(games, 2 * 10);
+        worker.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_stalled_worker() {
+        let executor = TcpExecutor::bind("127.0.0.1:0")
+            .unwrap()
+            .with_worker_timeout(Duration::from_millis(100));
+        let address = executor.address();
+
+        // a worker that receives its first job, but never replies or disconnects
+        let (stalled, took_job) = mpsc::channel();
+        let (done, finished) = mpsc::channel::<()>();
+        let staller = thread::spawn(move || {
+            let stream = TcpStream::connect(address).unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            stalled.send(()).unwrap();
+            let _ = finished.recv();
+        });
+        let worker = thread::spawn(move || {
+            took_job.recv().unwrap();
+            Worker::new(players()).connect(address)
+        });
+
+        let standings = play(executor);
+        let games: u32 = standings.records().iter().map(|r| r.games()).sum();
+        assert_eq!(games, 2 * 10);
+        worker.join().unwrap().unwrap();
+
+        drop(done);
+        staller.join().unwrap();
+    }
+}
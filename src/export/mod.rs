@@ -0,0 +1,456 @@
+//! Writers for tournament results in formats that external tools can read directly.
+
+mod table;
+
+pub use table::*;
+
+use crate::error::Error;
+use crate::game::{MatchResult, Outcome};
+use crate::history::{MatchHistory, MatchRecord};
+use crate::player::{PlayerData, PlayerInfo};
+use crate::scheduling::Round;
+use crate::scoring::Crosstable;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Escapes a PGN tag value.
+fn pgn_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The parameters of a player as `key=value` pairs, separated by `;`.
+fn params(info: &PlayerInfo) -> String {
+    info.params()
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+fn pgn_result(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::WinP1 => "1-0",
+        Outcome::WinP2 => "0-1",
+        Outcome::Draw => "1/2-1/2",
+    }
+}
+
+/// Writes the crosstable as a player × player matrix.
+///
+/// The header row contains the player names. Each row starts with the id, key, name and version
+/// of the row player, and lists the points it scored against each column player, followed by
+/// the total points and games. Cells of pairs that never met are left empty. The other metadata
+/// of the players is written by `write_players_csv`.
+pub fn write_crosstable_csv<W: Write>(mut writer: W, crosstable: &Crosstable) -> Result<(), Error> {
+    let players = crosstable.players();
+
+    write!(writer, "id,key,name,version")?;
+    for player in players {
+        write!(writer, ",{}", csv_field(player.name()))?;
+    }
+    writeln!(writer, ",points,games")?;
+
+    for (i, player) in players.iter().enumerate() {
+        let info = player.info();
+        write!(
+            writer,
+            "{},{},{},{}",
+            player.id(),
+            csv_field(info.key().unwrap_or_default()),
+            csv_field(info.name()),
+            csv_field(info.version().unwrap_or_default())
+        )?;
+        for j in 0..players.len() {
+            if crosstable.games(i, j) == 0 {
+                write!(writer, ",")?;
+            } else {
+                write!(writer, ",{}", crosstable.points(i, j))?;
+            }
+        }
+        writeln!(
+            writer,
+            ",{},{}",
+            crosstable.total_points(i),
+            crosstable.total_games(i)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes one row of metadata per player.
+///
+/// Tags are separated by `;`, parameters are written as `key=value` pairs separated by `;`.
+pub fn write_players_csv<'a, W, I>(mut writer: W, players: I) -> Result<(), Error>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a PlayerData>,
+{
+    writeln!(writer, "id,key,name,version,author,tags,params")?;
+    for player in players {
+        let info = player.info();
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{}",
+            player.id(),
+            csv_field(info.key().unwrap_or_default()),
+            csv_field(info.name()),
+            csv_field(info.version().unwrap_or_default()),
+            csv_field(info.author().unwrap_or_default()),
+            csv_field(&info.tags().join(";")),
+            csv_field(&params(info))
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a fixture list with one row per pairing, numbered by round and board.
+///
+/// Players are written by name, if they are among `players`, and by id otherwise. The player
+/// with the bye of a round is written in a row without a board and opponent.
+pub fn write_schedule_csv<'a, W, I>(
+    mut writer: W,
+    schedule: &[Round],
+    players: I,
+) -> Result<(), Error>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a PlayerData>,
+{
+    let names = players
+        .into_iter()
+        .map(|player| (player.id(), csv_field(player.name())))
+        .collect::<HashMap<_, _>>();
+    let name = |id| names.get(&id).cloned().unwrap_or_else(|| id.to_string());
+
+    writeln!(writer, "round,board,player1,player2")?;
+    for (i, round) in schedule.iter().enumerate() {
+        for (board, &(p1, p2)) in round.pairings.iter().enumerate() {
+            writeln!(writer, "{},{},{},{}", i + 1, board + 1, name(p1), name(p2))?;
+        }
+        if let Some(bye) = round.bye {
+            writeln!(writer, "{},,{},", i + 1, name(bye))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the match log as JSON lines, one serialized [`MatchRecord`] per line.
+#[cfg(feature = "serde")]
+pub fn write_jsonl<W, R>(mut writer: W, history: &MatchHistory<R>) -> Result<(), Error>
+where
+    W: Write,
+    R: serde::Serialize,
+{
+    for record in history.iter() {
+        serde_json::to_writer(&mut writer, record)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Writes a PGN-like game record for every match, without move text.
+pub fn write_pgn<W, R>(writer: W, history: &MatchHistory<R>) -> Result<(), Error>
+where
+    W: Write,
+    R: MatchResult,
+{
+    write_pgn_with_moves(writer, history, |_| String::new())
+}
+
+/// Writes a PGN-like game record for every match.
+///
+/// The metadata of the players is written as tags prefixed by their side, e.g. `WhiteVersion`.
+/// Tags and parameters are written like in `write_players_csv`. `movetext` renders the moves of a match. The result token is appended automatically.
+pub fn write_pgn_with_moves<W, R, F>(
+    mut writer: W,
+    history: &MatchHistory<R>,
+    movetext: F,
+) -> Result<(), Error>
+where
+    W: Write,
+    R: MatchResult,
+    F: Fn(&R) -> String,
+{
+    for MatchRecord {
+        number,
+        outcome,
+        result,
+    } in history.iter()
+    {
+        let unlabelled = result.outcome();
+        let (p1, p2) = outcome.sides(&unlabelled);
+        let token = pgn_result(&unlabelled);
+
+        writeln!(writer, "[Match \"{}\"]", number)?;
+        writeln!(writer, "[White \"{}\"]", pgn_value(p1.name()))?;
+        writeln!(writer, "[Black \"{}\"]", pgn_value(p2.name()))?;
+        writeln!(writer, "[WhiteId \"{}\"]", p1.id())?;
+        writeln!(writer, "[BlackId \"{}\"]", p2.id())?;
+        for (side, player) in [("White", p1), ("Black", p2)] {
+            let info = player.info();
+            if let Some(key) = info.key() {
+                writeln!(writer, "[{}Key \"{}\"]", side, pgn_value(key))?;
+            }
+            if let Some(version) = info.version() {
+                writeln!(writer, "[{}Version \"{}\"]", side, pgn_value(version))?;
+            }
+            if let Some(author) = info.author() {
+                writeln!(writer, "[{}Author \"{}\"]", side, pgn_value(author))?;
+            }
+            if !info.tags().is_empty() {
+                let tags = info.tags().join(";");
+                writeln!(writer, "[{}Tags \"{}\"]", side, pgn_value(&tags))?;
+            }
+            if !info.params().is_empty() {
+                writeln!(writer, "[{}Params \"{}\"]", side, pgn_value(&params(info)))?;
+            }
+        }
+        writeln!(writer, "[Result \"{}\"]", token)?;
+        writeln!(writer)?;
+
+        let moves = movetext(result);
+        if moves.is_empty() {
+            writeln!(writer, "{}", token)?;
+        } else {
+            writeln!(writer, "{} {}", moves.trim_end(), token)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::LabelledOutcome;
+    use crate::player::{PlayerGen, PlayerRepository};
+    use crate::test_util::{Scored, Unplayed};
+
+    fn players(players: Vec<PlayerGen<Unplayed>>) -> Vec<PlayerData> {
+        let mut repository = PlayerRepository::new();
+        players
+            .into_iter()
+            .map(|player| {
+                let id = repository.add_player(player);
+                repository.data(id).unwrap()
+            })
+            .collect()
+    }
+
+    fn output(write: impl FnOnce(&mut Vec<u8>) -> Result<(), Error>) -> String {
+        let mut out = Vec::new();
+        write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Players whose names need quoting or escaping.
+    fn awkward_players() -> Vec<PlayerData> {
+        players(vec![
+            PlayerGen::new(|| ()).with_name("Smith, J."),
+            PlayerGen::new(|| ()).with_name("The \"Bot\""),
+            PlayerGen::new(|| ()).with_name("back\\slash"),
+        ])
+    }
+
+    /// Player 0 beats player 1 as Player 1, and player 1 draws with player 2.
+    fn history(players: &[PlayerData]) -> MatchHistory<Scored> {
+        [
+            (
+                LabelledOutcome::Win {
+                    winner: players[0].clone(),
+                    loser: players[1].clone(),
+                },
+                Scored(Outcome::WinP1),
+            ),
+            (
+                LabelledOutcome::Draw(players[1].clone(), players[2].clone()),
+                Scored(Outcome::Draw),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_write_crosstable_csv() {
+        let history = history(&awkward_players());
+        let crosstable = Crosstable::from_outcomes(history.outcomes());
+
+        let csv = output(|out| write_crosstable_csv(out, &crosstable));
+        assert_eq!(
+            csv,
+            "id,key,name,version,\"Smith, J.\",\"The \"\"Bot\"\"\",back\\slash,points,games\n\
+             0,,\"Smith, J.\",,,1,,1,1\n\
+             1,,\"The \"\"Bot\"\"\",,0,,0.5,0.5,2\n\
+             2,,back\\slash,,,0.5,,0.5,1\n"
+        );
+    }
+
+    #[test]
+    fn test_write_pgn() {
+        let history = history(&awkward_players());
+
+        let pgn = output(|out| write_pgn(out, &history));
+        assert_eq!(
+            pgn,
+            "[Match \"1\"]\n\
+             [White \"Smith, J.\"]\n\
+             [Black \"The \\\"Bot\\\"\"]\n\
+             [WhiteId \"0\"]\n\
+             [BlackId \"1\"]\n\
+             [Result \"1-0\"]\n\
+             \n\
+             1-0\n\
+             \n\
+             [Match \"2\"]\n\
+             [White \"The \\\"Bot\\\"\"]\n\
+             [Black \"back\\\\slash\"]\n\
+             [WhiteId \"1\"]\n\
+             [BlackId \"2\"]\n\
+             [Result \"1/2-1/2\"]\n\
+             \n\
+             1/2-1/2\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn test_write_pgn_with_moves() {
+        let history = history(&awkward_players());
+
+        let pgn = output(|out| {
+            write_pgn_with_moves(out, &history, |result| match result.0 {
+                Outcome::Draw => String::new(),
+                _ => "1. e4 e5 2. Qh5 Nc6 ".to_string(),
+            })
+        });
+        let movetext = pgn
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('['))
+            .collect::<Vec<_>>();
+        // the result token follows the moves, and stands alone without moves
+        assert_eq!(movetext, ["1. e4 e5 2. Qh5 Nc6 1-0", "1/2-1/2"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_write_jsonl() {
+        let history = history(&awkward_players());
+
+        let jsonl = output(|out| write_jsonl(out, &history));
+        let records = jsonl
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["number"], 1);
+        assert_eq!(records[1]["number"], 2);
+        assert_eq!(records[1]["result"], "Draw");
+        assert!(records[0].to_string().contains(r#"The \"Bot\""#));
+    }
+
+    #[test]
+    fn test_write_players_csv() {
+        let players = players(vec![
+            PlayerGen::new(|| ()).with_name("random"),
+            PlayerGen::new(|| ())
+                .with_name("minimax")
+                .with_version("1.2")
+                .with_author("Ada")
+                .with_param("depth", 12)
+                .with_param("eval", "material")
+                .with_tag("search")
+                .with_tag("baseline"),
+        ]);
+
+        let csv = output(|out| write_players_csv(out, &players));
+        assert_eq!(
+            csv,
+            "id,key,name,version,author,tags,params\n\
+             0,,random,,,,\n\
+             1,,minimax,1.2,Ada,search;baseline,depth=12;eval=material\n"
+        );
+    }
+
+    /// A player without metadata, and a player with all kinds of metadata.
+    fn described_players() -> Vec<PlayerData> {
+        let mut repository = PlayerRepository::<Unplayed>::new();
+        repository.add_player(PlayerGen::new(|| ()).with_name("random"));
+        let id = repository
+            .add_player_with_key(
+                "minimax-v1",
+                PlayerGen::new(|| ())
+                    .with_name("minimax")
+                    .with_version("1.2")
+                    .with_author("Ada")
+                    .with_param("depth", 12)
+                    .with_tag("search")
+                    .with_tag("baseline"),
+            )
+            .unwrap();
+        vec![repository.data(0).unwrap(), repository.data(id).unwrap()]
+    }
+
+    #[test]
+    fn test_crosstable_metadata() {
+        let players = described_players();
+        let history = [(
+            LabelledOutcome::Draw(players[0].clone(), players[1].clone()),
+            Scored(Outcome::Draw),
+        )]
+        .into_iter()
+        .collect::<MatchHistory<_>>();
+        let crosstable = Crosstable::from_outcomes(history.outcomes());
+
+        let csv = output(|out| write_crosstable_csv(out, &crosstable));
+        assert_eq!(
+            csv,
+            "id,key,name,version,random,minimax,points,games\n\
+             0,,random,,,0.5,0.5,1\n\
+             1,minimax-v1,minimax,1.2,0.5,,0.5,1\n"
+        );
+    }
+
+    #[test]
+    fn test_pgn_tags() {
+        let players = described_players();
+        let history = [(
+            LabelledOutcome::Win {
+                winner: players[1].clone(),
+                loser: players[0].clone(),
+            },
+            Scored(Outcome::WinP2),
+        )]
+        .into_iter()
+        .collect::<MatchHistory<_>>();
+
+        let pgn = output(|out| write_pgn(out, &history));
+        assert_eq!(
+            pgn,
+            "[Match \"1\"]\n\
+             [White \"random\"]\n\
+             [Black \"minimax\"]\n\
+             [WhiteId \"0\"]\n\
+             [BlackId \"1\"]\n\
+             [BlackKey \"minimax-v1\"]\n\
+             [BlackVersion \"1.2\"]\n\
+             [BlackAuthor \"Ada\"]\n\
+             [BlackTags \"search;baseline\"]\n\
+             [BlackParams \"depth=12\"]\n\
+             [Result \"0-1\"]\n\
+             \n\
+             0-1\n\
+             \n"
+        );
+    }
+}
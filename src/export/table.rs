@@ -0,0 +1,251 @@
+use crate::error::Error;
+use crate::game::LabelledOutcome;
+use crate::player::{PlayerData, PlayerId};
+use crate::ranking::performance_ratings;
+use crate::scoring::{Crosstable, Standings};
+use std::fmt;
+
+/// A row of the [`Leaderboard`].
+#[derive(Debug, Clone)]
+pub struct LeaderboardRow {
+    pub rank: usize,
+    pub player: PlayerData,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub faults: u32,
+    /// Points, including wins by forfeit.
+    pub points: f64,
+    pub win_percentage: f64,
+    /// The performance rating from played matches only, see `performance_ratings`.
+    pub performance: f64,
+}
+
+/// The standings of a tournament, ready to be printed to a terminal.
+#[derive(Debug, Clone)]
+pub struct Leaderboard {
+    rows: Vec<LeaderboardRow>,
+}
+
+impl Leaderboard {
+    /// Combines the records of `standings` with the performance ratings of `crosstable`.
+    /// Both should be built from the same matches.
+    ///
+    /// The records count forfeits as wins and losses, while the crosstable and the performance
+    /// ratings only include played matches. A player that forfeited matches is therefore rated
+    /// on fewer games than its record shows.
+    ///
+    /// Returns an error if the performance ratings cannot be estimated.
+    pub fn new(standings: &Standings, crosstable: &Crosstable) -> Result<Self, Error> {
+        let ratings = performance_ratings(crosstable)?;
+
+        let rows = standings
+            .records()
+            .into_iter()
+            .enumerate()
+            .map(|(i, record)| LeaderboardRow {
+                rank: i + 1,
+                player: record.player.clone(),
+                wins: record.wins,
+                draws: record.draws,
+                losses: record.losses,
+                faults: record.faults,
+                points: record.points(),
+                win_percentage: record.win_percentage(),
+                performance: crosstable
+                    .index_of(record.player.id())
+                    .map_or(f64::NAN, |i| ratings[i]),
+            })
+            .collect();
+
+        Ok(Leaderboard { rows })
+    }
+
+    /// Builds the leaderboard from a sequence of match outcomes.
+    pub fn from_outcomes<'a, I>(outcomes: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = &'a LabelledOutcome>,
+    {
+        let outcomes = outcomes.into_iter().collect::<Vec<_>>();
+        Leaderboard::new(
+            &Standings::from_outcomes(outcomes.iter().copied()),
+            &Crosstable::from_outcomes(outcomes),
+        )
+    }
+
+    /// Reorders the rows to follow `order`, e.g. as returned by a `RankingPolicy`.
+    /// Players missing from `order` are placed last.
+    pub fn ranked_by(mut self, order: &[PlayerId]) -> Self {
+        self.rows.sort_by_key(|row| {
+            order
+                .iter()
+                .position(|&id| id == row.player.id())
+                .unwrap_or(order.len())
+        });
+        self.rerank();
+        self
+    }
+
+    /// Reorders the rows by descending performance rating.
+    pub fn ranked_by_performance(mut self) -> Self {
+        self.rows
+            .sort_by(|a, b| b.performance.total_cmp(&a.performance));
+        self.rerank();
+        self
+    }
+
+    fn rerank(&mut self) {
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            row.rank = i + 1;
+        }
+    }
+
+    pub fn rows(&self) -> &[LeaderboardRow] {
+        &self.rows
+    }
+}
+
+fn name_width<'a>(players: impl Iterator<Item = &'a PlayerData>) -> usize {
+    players
+        .map(|player| player.name().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Player".len())
+}
+
+impl fmt::Display for Leaderboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = name_width(self.rows.iter().map(|row| &row.player));
+
+        writeln!(
+            f,
+            "{:>4}  {:<width$}  {:>4}  {:>4}  {:>4}  {:>4}  {:>6}  {:>6}  {:>6}",
+            "Rank", "Player", "W", "D", "L", "F", "Pts", "Win%", "Perf"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:>4}  {:<width$}  {:>4}  {:>4}  {:>4}  {:>4}  {:>6.1}  {:>6.1}  {:>6.0}",
+                row.rank,
+                row.player.name(),
+                row.wins,
+                row.draws,
+                row.losses,
+                row.faults,
+                row.points,
+                row.win_percentage,
+                row.performance
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the head-to-head matrix. Opponents are referred to by their row number.
+impl fmt::Display for Crosstable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let players = self.players();
+        let width = name_width(players.iter());
+
+        write!(f, "{:>3}  {:<width$}", "#", "Player")?;
+        for j in 0..players.len() {
+            write!(f, "  {:>4}", j + 1)?;
+        }
+        writeln!(f, "  {:>6}", "Pts")?;
+
+        for (i, player) in players.iter().enumerate() {
+            write!(f, "{:>3}  {:<width$}", i + 1, player.name())?;
+            for j in 0..players.len() {
+                if i == j {
+                    write!(f, "  {:>4}", "X")?;
+                } else if self.games(i, j) == 0 {
+                    write!(f, "  {:>4}", "-")?;
+                } else {
+                    write!(f, "  {:>4}", self.points(i, j))?;
+                }
+            }
+            writeln!(f, "  {:>6.1}", self.total_points(i))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{PlayerGen, PlayerRepository};
+    use crate::test_util::Unplayed;
+
+    /// alpha beats beta and gamma, who draw.
+    fn outcomes() -> Vec<LabelledOutcome> {
+        let mut repository = PlayerRepository::<Unplayed>::new();
+        let players = ["alpha", "beta", "gamma"]
+            .into_iter()
+            .map(|name| {
+                let id = repository.add_player(PlayerGen::new(|| ()).with_name(name));
+                repository.data(id).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        vec![
+            LabelledOutcome::Win {
+                winner: players[0].clone(),
+                loser: players[1].clone(),
+            },
+            LabelledOutcome::Draw(players[1].clone(), players[2].clone()),
+            LabelledOutcome::Win {
+                winner: players[0].clone(),
+                loser: players[2].clone(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let leaderboard = Leaderboard::from_outcomes(&outcomes()).unwrap();
+        let lines = leaderboard.to_string();
+        let lines = lines.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[0],
+            "Rank  Player     W     D     L     F     Pts    Win%    Perf"
+        );
+        // the performance column is checked through the rows
+        assert_eq!(
+            lines[1..]
+                .iter()
+                .map(|line| &line[..52])
+                .collect::<Vec<_>>(),
+            [
+                "   1  alpha      2     0     0     0     2.0   100.0",
+                "   2  beta       0     1     1     0     0.5     0.0",
+                "   3  gamma      0     1     1     0     0.5     0.0",
+            ]
+        );
+
+        let rows = leaderboard.rows();
+        assert!(rows[0].performance > 1500.0);
+        assert!((rows[1].performance - rows[2].performance).abs() < 1e-3);
+        assert_eq!(lines[1][52..].trim(), format!("{:.0}", rows[0].performance));
+
+        let reranked = leaderboard.ranked_by(&[2, 0]);
+        let order = reranked
+            .rows()
+            .iter()
+            .map(|row| (row.rank, row.player.id()))
+            .collect::<Vec<_>>();
+        assert_eq!(order, [(1, 2), (2, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn test_crosstable() {
+        let crosstable = Crosstable::from_outcomes(&outcomes());
+        assert_eq!(
+            crosstable.to_string(),
+            "  #  Player     1     2     3     Pts\n\
+             \x20 1  alpha      X     1     1     2.0\n\
+             \x20 2  beta       0     X   0.5     0.5\n\
+             \x20 3  gamma      0   0.5     X     0.5\n"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! Agents that run as external processes, e.g. engines written in Python or C++.
+//!
+//! The agent talks to the process over stdin/stdout, one command per line:
+//!
+//! | Tournament sends          | Process replies  |
+//! |---------------------------|------------------|
+//! | `hello 1`                 | `hello [name]`   |
+//! | `newgame <1\|2>`          | `ok`             |
+//! | `move <time_ms> <state>`  | `move <move>`    |
+//! | `quit`                    | (exits)          |
+//!
+//! `hello` is sent once after the process is started, with the protocol version. `newgame`
+//! tells the process which side it plays. `move` requests a move for the given game state, with
+//! the remaining thinking time in milliseconds. The encoding of states and moves is up to the
+//! `Match` implementation. The process may reply `error <message>` to any command. Lines
+//! starting with `#` are ignored, and can be used for logging.
+
+use crate::error::Error;
+use crate::game::{FaultKind, Side};
+use std::ffi::OsString;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PROTOCOL_VERSION: u32 = 1;
+
+/// The ways an external agent can fail.
+#[derive(Debug)]
+pub enum ExternalAgentError {
+    /// The process could not be started.
+    Spawn(std::io::Error),
+    /// The process did not reply within the time limit, and has been killed.
+    Timeout(Duration),
+    /// The process exited or closed its stdout. The exit status is included, if known.
+    Crashed(Option<ExitStatus>),
+    /// The process replied with an error or an unexpected line.
+    Protocol(String),
+}
+
+impl fmt::Display for ExternalAgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalAgentError::Spawn(e) => write!(f, "external agent could not be started: {}", e),
+            ExternalAgentError::Timeout(limit) => {
+                write!(f, "external agent did not reply within {:?}", limit)
+            }
+            ExternalAgentError::Crashed(Some(status)) => {
+                write!(f, "external agent crashed ({})", status)
+            }
+            ExternalAgentError::Crashed(None) => write!(f, "external agent crashed"),
+            ExternalAgentError::Protocol(msg) => {
+                write!(f, "external agent protocol error: {}", msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExternalAgentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExternalAgentError::Spawn(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Timeouts become `Error::Timeout`, all other failures `Error::Match`, where crashes are of
+/// kind `FaultKind::Crash`. As the agent does not know its side, the match attributes the
+/// error with `Error::with_side`:
+///
+/// ```ignore
+/// let reply = agent
+///     .request_move(&state, time_left)
+///     .map_err(|e| Error::from(e).with_side(Side::Player1))?;
+/// ```
+impl From<ExternalAgentError> for Error {
+    fn from(error: ExternalAgentError) -> Self {
+        match error {
+            ExternalAgentError::Timeout(limit) => Error::Timeout { side: None, limit },
+            error @ (ExternalAgentError::Spawn(_) | ExternalAgentError::Crashed(_)) => {
+                Error::Match {
+                    side: None,
+                    kind: FaultKind::Crash,
+                    source: error.into(),
+                }
+            }
+            error => Error::match_failed(error),
+        }
+    }
+}
+
+/// A running external process.
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+/// An agent that is played by an external executable.
+///
+/// The process is started lazily by the first command, so that agents can be created by an
+/// infallible `PlayerGen` generator. Errors while starting the process are reported by that
+/// first command. Combined with a pooled player, the process is kept alive across matches.
+pub struct ExternalAgent {
+    program: OsString,
+    args: Vec<OsString>,
+    startup_timeout: Duration,
+    grace: Duration,
+    process: Option<Process>,
+    name: Option<String>,
+}
+
+impl ExternalAgent {
+    /// Creates an agent that runs `program`. The process is not started yet.
+    pub fn new(program: impl Into<OsString>) -> Self {
+        ExternalAgent {
+            program: program.into(),
+            args: Vec::new(),
+            startup_timeout: Duration::from_secs(10),
+            grace: Duration::from_millis(500),
+            process: None,
+            name: None,
+        }
+    }
+
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets how long the process may take to start and answer the handshake.
+    /// The default is 10 seconds.
+    pub fn with_startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Sets the time a process may exceed its thinking time, e.g. due to process overhead,
+    /// before it is killed. The default is 500 milliseconds.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// The name the process announced in the handshake, if it has been started.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Starts the process and performs the handshake, unless it is already running.
+    pub fn start(&mut self) -> Result<(), ExternalAgentError> {
+        if self.process.is_some() {
+            return Ok(());
+        }
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(ExternalAgentError::Spawn)?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // Read lines on a separate thread, so that replies can be awaited with a timeout.
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.process = Some(Process {
+            child,
+            stdin,
+            lines,
+        });
+
+        let reply = self.command(&format!("hello {}", PROTOCOL_VERSION), self.startup_timeout)?;
+        let name = Self::expect_reply(&reply, "hello")?;
+        self.name = (!name.is_empty()).then(|| name.to_string());
+        Ok(())
+    }
+
+    /// Tells the process that a new game starts, and which side it plays.
+    pub fn new_game(&mut self, side: Side) -> Result<(), ExternalAgentError> {
+        self.start()?;
+        let side = match side {
+            Side::Player1 => 1,
+            Side::Player2 => 2,
+        };
+        let reply = self.command(&format!("newgame {}", side), self.startup_timeout)?;
+        Self::expect_reply(&reply, "ok").map(|_| ())
+    }
+
+    /// Requests a move for `state`, which must fit on a single line.
+    ///
+    /// The process is killed if it does not reply within `time_left` plus the grace period.
+    pub fn request_move(
+        &mut self,
+        state: &str,
+        time_left: Duration,
+    ) -> Result<String, ExternalAgentError> {
+        self.start()?;
+        let command = format!("move {} {}", time_left.as_millis(), state);
+        let reply = self.command(&command, time_left + self.grace)?;
+        Self::expect_reply(&reply, "move").map(str::to_string)
+    }
+
+    /// Asks the process to exit, and kills it if it does not exit within the grace period.
+    pub fn quit(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = writeln!(process.stdin, "quit");
+            let _ = process.stdin.flush();
+
+            let deadline = Instant::now() + self.grace;
+            while Instant::now() < deadline {
+                if let Ok(Some(_)) = process.child.try_wait() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            let _ = process.child.kill();
+            let _ = process.child.wait();
+        }
+    }
+
+    /// Sends a command and waits for the first reply line that is not a comment.
+    fn command(&mut self, command: &str, timeout: Duration) -> Result<String, ExternalAgentError> {
+        let process = self
+            .process
+            .as_mut()
+            .expect("external agent process is running");
+
+        let sent = writeln!(process.stdin, "{}", command).and_then(|_| process.stdin.flush());
+        if sent.is_err() {
+            return Err(self.crashed());
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match process.lines.recv_timeout(remaining) {
+                Ok(line) if line.starts_with('#') || line.trim().is_empty() => continue,
+                Ok(line) => return Ok(line),
+                Err(RecvTimeoutError::Timeout) => {
+                    self.kill();
+                    return Err(ExternalAgentError::Timeout(timeout));
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(self.crashed()),
+            }
+        }
+    }
+
+    /// Checks that `reply` starts with `keyword`, and returns the rest of the line.
+    fn expect_reply<'a>(reply: &'a str, keyword: &str) -> Result<&'a str, ExternalAgentError> {
+        let (head, rest) = reply.split_once(' ').unwrap_or((reply, ""));
+        if head == keyword {
+            Ok(rest.trim())
+        } else if head == "error" {
+            Err(ExternalAgentError::Protocol(rest.trim().to_string()))
+        } else {
+            Err(ExternalAgentError::Protocol(format!(
+                "expected '{}', got '{}'",
+                keyword, reply
+            )))
+        }
+    }
+
+    /// Reaps the process after it has stopped responding, and reports its exit status.
+    fn crashed(&mut self) -> ExternalAgentError {
+        let status = self.process.take().and_then(|mut process| {
+            // give the process a moment to exit, as stdout may close slightly earlier
+            let deadline = Instant::now() + self.grace;
+            loop {
+                match process.child.try_wait() {
+                    Ok(Some(status)) => return Some(status),
+                    Ok(None) if Instant::now() < deadline => {
+                        thread::sleep(Duration::from_millis(5))
+                    }
+                    _ => {
+                        let _ = process.child.kill();
+                        return process.child.wait().ok();
+                    }
+                }
+            }
+        });
+        ExternalAgentError::Crashed(status)
+    }
+
+    fn kill(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            let _ = process.child.kill();
+            let _ = process.child.wait();
+        }
+    }
+}
+
+impl Drop for ExternalAgent {
+    fn drop(&mut self) {
+        self.quit();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// An agent running a shell script as its process.
+    fn script(body: &str) -> ExternalAgent {
+        ExternalAgent::new("sh")
+            .with_args(["-c", body])
+            .with_grace(Duration::from_millis(200))
+    }
+
+    const ECHO: &str = r##"
+        while read cmd args; do
+            case $cmd in
+                hello) echo "# starting"; echo "hello echo";;
+                newgame) echo ok;;
+                move) echo "move ${args#* }";;
+                quit) exit 0;;
+            esac
+        done
+    "##;
+
+    #[test]
+    fn test_protocol() {
+        let mut agent = script(ECHO);
+        agent.new_game(Side::Player1).unwrap();
+        assert_eq!(agent.name(), Some("echo"));
+
+        let reply = agent
+            .request_move("e2e4 e7e5", Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(reply, "e2e4 e7e5");
+
+        agent.new_game(Side::Player2).unwrap();
+        let reply = agent.request_move("d4", Duration::from_secs(1)).unwrap();
+        assert_eq!(reply, "d4");
+        agent.quit();
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut agent = script(
+            r##"
+            while read cmd args; do
+                case $cmd in
+                    hello) echo hello;;
+                    newgame) echo ok;;
+                    move) sleep 5;;
+                esac
+            done
+        "##,
+        );
+        agent.new_game(Side::Player1).unwrap();
+
+        let start = Instant::now();
+        let err = agent
+            .request_move("state", Duration::from_millis(100))
+            .unwrap_err();
+        assert!(matches!(err, ExternalAgentError::Timeout(_)), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_crash() {
+        let mut agent = script(
+            r##"
+            while read cmd args; do
+                case $cmd in
+                    hello) echo hello;;
+                    newgame) echo ok;;
+                    move) exit 3;;
+                esac
+            done
+        "##,
+        );
+        agent.new_game(Side::Player1).unwrap();
+
+        let err = agent
+            .request_move("state", Duration::from_secs(1))
+            .unwrap_err();
+        match err {
+            ExternalAgentError::Crashed(Some(status)) => assert_eq!(status.code(), Some(3)),
+            err => panic!("expected crash, got {}", err),
+        }
+    }
+
+    #[test]
+    fn test_error_reply() {
+        let mut agent = script(
+            r##"
+            while read cmd args; do
+                case $cmd in
+                    hello) echo hello;;
+                    newgame) echo "error unsupported side";;
+                esac
+            done
+        "##,
+        );
+        let err = agent.new_game(Side::Player2).unwrap_err();
+        assert!(matches!(err, ExternalAgentError::Protocol(msg) if msg == "unsupported side"));
+    }
+
+    #[test]
+    fn test_spawn_failure() {
+        let mut agent = ExternalAgent::new("/nonexistent/engine");
+        let err = agent.new_game(Side::Player1).unwrap_err();
+        assert!(matches!(err, ExternalAgentError::Spawn(_)));
+    }
+}
@@ -1,5 +1,4 @@
 use crate::player::{Player, PlayerData};
-use anyhow::Error;
 use std::fmt::Debug;
 
 pub trait MatchResult {
@@ -7,13 +6,74 @@ pub trait MatchResult {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Outcome {
     WinP1,
     WinP2,
     Draw,
 }
 
+/// One of the two sides of a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    Player1,
+    Player2,
+}
+
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::Player1 => Side::Player2,
+            Side::Player2 => Side::Player1,
+        }
+    }
+}
+
+/// The ways a player can be at fault for a match that could not be completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FaultKind {
+    IllegalMove,
+    Crash,
+    Timeout,
+    Other,
+}
+
+/// A match that was aborted because the player on `side` was at fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fault {
+    pub side: Side,
+    pub kind: FaultKind,
+}
+
+impl Fault {
+    /// Attaches the participants of the match, given as player 1 and player 2.
+    pub fn label(self, player1: PlayerData, player2: PlayerData) -> LabelledFault {
+        let (player, opponent) = match self.side {
+            Side::Player1 => (player1, player2),
+            Side::Player2 => (player2, player1),
+        };
+        LabelledFault {
+            player,
+            opponent,
+            kind: self.kind,
+        }
+    }
+}
+
+/// A fault, with the player at fault and the opponent, who is awarded a forfeit win.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelledFault {
+    pub player: PlayerData,
+    pub opponent: PlayerData,
+    pub kind: FaultKind,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LabelledOutcome {
     Win {
         winner: PlayerData,
@@ -36,6 +96,23 @@ impl LabelledOutcome {
             Outcome::Draw => LabelledOutcome::Draw(player1, player2),
         }
     }
+
+    /// Returns both participants. For a win, the winner comes first.
+    pub fn players(&self) -> (&PlayerData, &PlayerData) {
+        match self {
+            LabelledOutcome::Win { winner, loser } => (winner, loser),
+            LabelledOutcome::Draw(p1, p2) => (p1, p2),
+        }
+    }
+
+    /// Recovers which participant played as player 1 and which as player 2,
+    /// given the unlabelled outcome of the same match.
+    pub fn sides(&self, outcome: &Outcome) -> (&PlayerData, &PlayerData) {
+        match (self, outcome) {
+            (LabelledOutcome::Win { winner, loser }, Outcome::WinP2) => (loser, winner),
+            _ => self.players(),
+        }
+    }
 }
 
 pub trait Match: Sized {
@@ -54,5 +131,54 @@ pub trait Match: Sized {
 
     /// Play the match and return the result.
     /// If the match cannot be played, return an error.
-    fn playout(&mut self) -> Result<Self::MatchResult, Error>;
+    ///
+    /// To attribute the failure to one of the players, return a `crate::error::Error`, e.g.
+    /// `Error::illegal_move(Side::Player2, ..).into()`. Any other error is a match failure that
+    /// neither player is at fault for.
+    fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error>;
+
+    /// Seeds the randomness of the match. Called before `playout`.
+    ///
+    /// Every match of a tournament is assigned a seed, so that it can be replayed regardless of
+    /// where it is executed. Ignored by default.
+    fn set_seed(&mut self, _seed: u64) {}
+}
+
+/// A `Match` that hands its agents back after it has been played. Required to pool the agents
+/// of a player, see `PlayerGen::pooled`, and to give feedback to the agents, see
+/// `PlayerGen::on_agent_feedback`.
+pub trait IntoAgents: Match {
+    /// Hands the agents back after the match has been played, player 1 first.
+    ///
+    /// Agents of pooled players that are returned here are reused in later matches. Agents
+    /// that are not returned are dropped, e.g. because they crashed, and replaced by newly
+    /// generated agents.
+    fn into_agents(self) -> (Option<Self::Agent>, Option<Self::Agent>);
+}
+
+/// A `Match` that can be played asynchronously, e.g. because its agents query a server.
+///
+/// Async matches are played by an `AsyncTournament`. As they remain a `Match`, `playout` can
+/// block on `playout_async`, so that the match can still be played by a `Tournament`.
+#[cfg(feature = "async")]
+pub trait AsyncMatch: Match + Send {
+    /// Play the match and return the result.
+    /// If the match cannot be played, return an error, see `Match::playout`.
+    fn playout_async(
+        &mut self,
+    ) -> impl std::future::Future<Output = Result<Self::MatchResult, anyhow::Error>> + Send;
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_outcome() {
+        for outcome in [Outcome::WinP1, Outcome::WinP2, Outcome::Draw] {
+            let json = serde_json::to_string(&outcome).unwrap();
+            let restored: Outcome = serde_json::from_str(&json).unwrap();
+            assert_eq!(restored, outcome);
+        }
+    }
 }
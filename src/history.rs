@@ -0,0 +1,122 @@
+use crate::game::LabelledOutcome;
+
+/// A single finished match, as recorded in a [`MatchHistory`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchRecord<R> {
+    /// The position of the match in the history, starting at 1.
+    pub number: usize,
+    pub outcome: LabelledOutcome,
+    pub result: R,
+}
+
+/// The log of all finished matches of a tournament, in the order they were reported.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchHistory<R> {
+    records: Vec<MatchRecord<R>>,
+}
+
+impl<R> MatchHistory<R> {
+    pub fn new() -> Self {
+        MatchHistory {
+            records: Vec::new(),
+        }
+    }
+
+    /// Appends a finished match to the history.
+    pub fn push(&mut self, outcome: LabelledOutcome, result: R) {
+        let number = self.records.len() + 1;
+        self.records.push(MatchRecord {
+            number,
+            outcome,
+            result,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn records(&self) -> &[MatchRecord<R>] {
+        &self.records
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MatchRecord<R>> {
+        self.records.iter()
+    }
+
+    /// Returns the outcomes of all matches, in order.
+    pub fn outcomes(&self) -> impl Iterator<Item = &LabelledOutcome> {
+        self.records.iter().map(|record| &record.outcome)
+    }
+}
+
+impl<R> Default for MatchHistory<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> FromIterator<(LabelledOutcome, R)> for MatchHistory<R> {
+    fn from_iter<I: IntoIterator<Item = (LabelledOutcome, R)>>(iter: I) -> Self {
+        let mut history = MatchHistory::new();
+        history.extend(iter);
+        history
+    }
+}
+
+impl<R> Extend<(LabelledOutcome, R)> for MatchHistory<R> {
+    fn extend<I: IntoIterator<Item = (LabelledOutcome, R)>>(&mut self, iter: I) {
+        for (outcome, result) in iter {
+            self.push(outcome, result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::players;
+
+    #[test]
+    fn test_match_history() {
+        let players = players(3);
+        let win = |winner: usize, loser: usize| LabelledOutcome::Win {
+            winner: players[winner].clone(),
+            loser: players[loser].clone(),
+        };
+
+        let mut history = MatchHistory::new();
+        assert!(history.is_empty());
+        history.push(win(0, 1), "first");
+        history.extend([(win(2, 0), "second"), (win(1, 2), "third")]);
+
+        // matches are numbered in the order they are reported, starting at 1
+        assert_eq!(history.len(), 3);
+        let numbers = history
+            .iter()
+            .map(|record| record.number)
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, [1, 2, 3]);
+        let results = history
+            .records()
+            .iter()
+            .map(|record| record.result)
+            .collect::<Vec<_>>();
+        assert_eq!(results, ["first", "second", "third"]);
+
+        let winners = history
+            .outcomes()
+            .map(|outcome| outcome.players().0.id())
+            .collect::<Vec<_>>();
+        assert_eq!(winners, [0, 2, 1]);
+
+        let collected = [(win(1, 0), ())].into_iter().collect::<MatchHistory<_>>();
+        assert_eq!(collected.records()[0].number, 1);
+    }
+}
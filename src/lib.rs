@@ -1,10 +1,19 @@
 #![allow(dead_code)]
 
+pub mod error;
+pub mod executor;
+pub mod export;
+pub mod external;
 pub mod game;
+pub mod history;
 pub mod player;
 pub mod ranking;
+#[cfg(feature = "runner")]
+pub mod runner;
 pub mod scheduling;
 pub mod scoring;
+#[cfg(test)]
+pub(crate) mod test_util;
 pub mod tournaments;
 
 pub mod prelude {
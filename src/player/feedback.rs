@@ -0,0 +1,159 @@
+use crate::game::{LabelledOutcome, Match};
+use crate::player::PlayerData;
+
+pub(crate) type FeedbackHook<M> = Box<dyn Fn(&Feedback<M>) + Send + Sync>;
+pub(crate) type AgentFeedbackHook<M> =
+    Box<dyn Fn(&mut <M as Match>::Agent, &Feedback<M>) + Send + Sync>;
+
+/// The result of a finished match, from the perspective of one of its participants.
+pub struct Feedback<'a, M: Match> {
+    pub player: &'a PlayerData,
+    pub opponent: &'a PlayerData,
+    pub outcome: &'a LabelledOutcome,
+    pub result: &'a M::MatchResult,
+}
+
+impl<'a, M: Match> Feedback<'a, M> {
+    pub fn won(&self) -> bool {
+        matches!(self.outcome, LabelledOutcome::Win { winner, .. } if winner.id() == self.player.id())
+    }
+
+    pub fn lost(&self) -> bool {
+        matches!(self.outcome, LabelledOutcome::Win { loser, .. } if loser.id() == self.player.id())
+    }
+
+    pub fn drew(&self) -> bool {
+        matches!(self.outcome, LabelledOutcome::Draw(..))
+    }
+
+    /// 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn score(&self) -> f64 {
+        if self.won() {
+            1.0
+        } else if self.drew() {
+            0.5
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::InlineExecutor;
+    use crate::game::{IntoAgents, MatchResult, Outcome};
+    use crate::player::{Player, PlayerGen, PlayerId, PlayerRepository};
+    use crate::scheduling::RoundRobbinScheduler;
+    use crate::scoring::Standings;
+    use crate::tournaments::Tournament;
+    use std::sync::{Arc, Mutex};
+
+    /// Remembers the feedback it received, as (player, opponent, score).
+    #[derive(Default)]
+    struct Learner {
+        strength: u32,
+        seen: Vec<(PlayerId, PlayerId, f64)>,
+    }
+
+    /// The stronger agent wins.
+    struct Duel(Learner, Learner);
+
+    #[derive(Debug)]
+    struct DuelResult(Outcome);
+
+    impl MatchResult for DuelResult {
+        fn outcome(&self) -> Outcome {
+            self.0.clone()
+        }
+    }
+
+    impl Match for Duel {
+        type Agent = Learner;
+        type MatchResult = DuelResult;
+
+        fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+            Duel(player1.unpack(), player2.unpack())
+        }
+
+        fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+            Ok(DuelResult(if self.0.strength > self.1.strength {
+                Outcome::WinP1
+            } else {
+                Outcome::WinP2
+            }))
+        }
+    }
+
+    impl IntoAgents for Duel {
+        fn into_agents(self) -> (Option<Learner>, Option<Learner>) {
+            (Some(self.0), Some(self.1))
+        }
+    }
+
+    fn learner(strength: u32) -> PlayerGen<Duel> {
+        PlayerGen::new(move || Learner {
+            strength,
+            ..Learner::default()
+        })
+    }
+
+    fn record(feedback: &Feedback<Duel>) -> (PlayerId, PlayerId, f64) {
+        (
+            feedback.player.id(),
+            feedback.opponent.id(),
+            feedback.score(),
+        )
+    }
+
+    #[test]
+    fn test_feedback_hooks() {
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let recorded = observed.clone();
+
+        let mut players = PlayerRepository::new();
+        let learning =
+            players.add_player(learner(2).pooled().on_agent_feedback(
+                |agent: &mut Learner, feedback| agent.seen.push(record(feedback)),
+            ));
+        let observing = players.add_player(
+            learner(1).on_feedback(move |feedback| recorded.lock().unwrap().push(record(feedback))),
+        );
+        let strongest = players.add_player(learner(3));
+
+        let scheduler = RoundRobbinScheduler::new(players.ids())
+            .with_cycles(2)
+            .unwrap();
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        tournament.init();
+        assert_eq!(tournament.by_ref().count(), 6);
+
+        // every player plays both sides against both opponents, and only hears about its own
+        // results
+        let mut observed = observed.lock().unwrap().clone();
+        observed.sort_by_key(|seen| seen.1);
+        assert_eq!(
+            observed,
+            [
+                (observing, learning, 0.0),
+                (observing, learning, 0.0),
+                (observing, strongest, 0.0),
+                (observing, strongest, 0.0),
+            ]
+        );
+
+        let player = tournament.players().get_raw(learning).unwrap();
+        let mut agent = player.pool().unwrap().checkout().unwrap();
+        agent.seen.sort_by_key(|seen| seen.1);
+        assert_eq!(
+            agent.seen,
+            [
+                (learning, observing, 1.0),
+                (learning, observing, 1.0),
+                (learning, strongest, 0.0),
+                (learning, strongest, 0.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,748 @@
+mod feedback;
+mod pool;
+
+pub use feedback::Feedback;
+pub use pool::*;
+
+use feedback::{AgentFeedbackHook, FeedbackHook};
+
+use crate::error::Error;
+use crate::game::{IntoAgents, Match};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+pub type PlayerId = usize;
+
+/// Takes the agents out of a played match, see `IntoAgents`.
+type RecoverAgents<M> = fn(M) -> (Option<<M as Match>::Agent>, Option<<M as Match>::Agent>);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerInfo {
+    name: Cow<'static, str>,
+    /// A user-provided identifier, that is stable across runs.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    key: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    version: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    author: Option<String>,
+    /// Hyperparameters and other free-form key/value metadata.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    params: BTreeMap<String, String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    tags: Vec<String>,
+}
+
+impl PlayerInfo {
+    fn new(name: Cow<'static, str>) -> Self {
+        PlayerInfo {
+            name,
+            key: None,
+            version: None,
+            author: None,
+            params: BTreeMap::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn params(&self) -> &BTreeMap<String, String> {
+        &self.params
+    }
+
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(String::as_str)
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Creates the agents of a player.
+///
+/// Implemented for all `Fn() -> M::Agent + Send + Sync` closures, so generators can capture
+/// parameters such as a search depth, or shared resources such as network weights.
+pub trait AgentFactory<M: Match>: Send + Sync {
+    fn create(&self) -> M::Agent;
+}
+
+impl<M, F> AgentFactory<M> for F
+where
+    M: Match,
+    F: Fn() -> M::Agent + Send + Sync,
+{
+    fn create(&self) -> M::Agent {
+        self()
+    }
+}
+
+pub struct PlayerGen<M: Match> {
+    generator: Arc<dyn AgentFactory<M>>,
+    info: Arc<PlayerInfo>,
+    pool: Option<Arc<AgentPool<M>>>,
+    feedback: Option<Arc<FeedbackHook<M>>>,
+    agent_feedback: Option<Arc<AgentFeedbackHook<M>>>,
+    /// Set if the agents are needed after a match, for pooling or feedback.
+    recover: Option<RecoverAgents<M>>,
+}
+
+// impl clone for PlayerGen
+impl<M: Match> Clone for PlayerGen<M> {
+    fn clone(&self) -> Self {
+        PlayerGen {
+            generator: self.generator.clone(),
+            info: self.info.clone(),
+            pool: self.pool.clone(),
+            feedback: self.feedback.clone(),
+            agent_feedback: self.agent_feedback.clone(),
+            recover: self.recover,
+        }
+    }
+}
+
+// impl PlayerGenerator<M: Match>
+impl<M: Match> PlayerGen<M> {
+    /// Creates a player from a function or closure, that is called whenever an agent is needed.
+    pub fn new<F>(agent_generator: F) -> Self
+    where
+        F: Fn() -> M::Agent + Send + Sync + 'static,
+    {
+        PlayerGen::from_factory(agent_generator)
+    }
+
+    pub fn from_factory<F: AgentFactory<M> + 'static>(factory: F) -> Self {
+        PlayerGen {
+            generator: Arc::new(factory),
+            info: Arc::new(PlayerInfo::new(Cow::Borrowed(std::any::type_name::<
+                M::Agent,
+            >()))),
+            pool: None,
+            feedback: None,
+            agent_feedback: None,
+            recover: None,
+        }
+    }
+
+    /// Creates a player whose agents are clones of `prototype`.
+    pub fn from_prototype(prototype: M::Agent) -> Self
+    where
+        M::Agent: Clone + Sync + 'static,
+    {
+        PlayerGen::new(move || prototype.clone())
+    }
+
+    /// Creates a player whose agents are built from a resource that is shared between all
+    /// of them, such as network weights that are expensive to load.
+    pub fn from_shared<T, F>(resource: Arc<T>, build: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> M::Agent + Send + Sync + 'static,
+    {
+        PlayerGen::new(move || build(&resource))
+    }
+
+    /// Sets the display name. Accepts both static strings and names built at runtime.
+    pub fn with_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.info_mut().name = name.into();
+        self
+    }
+
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.info_mut().version = Some(version.into());
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.info_mut().author = Some(author.into());
+        self
+    }
+
+    /// Records a hyperparameter or other key/value metadata, replacing any previous value.
+    pub fn with_param(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.info_mut().params.insert(key.into(), value.to_string());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        let tag = tag.into();
+        if !self.info.has_tag(&tag) {
+            self.info_mut().tags.push(tag);
+        }
+        self
+    }
+
+    pub fn info(&self) -> &PlayerInfo {
+        &self.info
+    }
+
+    fn info_mut(&mut self) -> &mut PlayerInfo {
+        Arc::make_mut(&mut self.info)
+    }
+
+    /// Keeps agents alive across matches, instead of generating a new agent for every match.
+    /// This pays off for agents with an expensive setup, such as loading weights.
+    ///
+    /// Agents are reused if the `Match` hands them back in `IntoAgents::into_agents`.
+    pub fn pooled(self) -> Self
+    where
+        M: IntoAgents,
+    {
+        PlayerGen {
+            pool: Some(Arc::new(AgentPool::new())),
+            recover: Some(M::into_agents),
+            ..self
+        }
+    }
+
+    /// Like `pooled`, but calls `reset` on every agent before it is returned to the pool,
+    /// e.g. to clear per-game state.
+    pub fn pooled_with_reset<F>(self, reset: F) -> Self
+    where
+        M: IntoAgents,
+        F: Fn(&mut M::Agent) + Send + Sync + 'static,
+    {
+        PlayerGen {
+            pool: Some(Arc::new(AgentPool::with_reset(reset))),
+            recover: Some(M::into_agents),
+            ..self
+        }
+    }
+
+    /// Returns the agent pool, if the player is pooled.
+    pub fn pool(&self) -> Option<&AgentPool<M>> {
+        self.pool.as_deref()
+    }
+
+    /// Calls `hook` with the result of every finished match of the player, e.g. to let a
+    /// shared model learn online. Replaces any previous hook.
+    ///
+    /// The hook runs on the thread that played the match.
+    pub fn on_feedback<F>(self, hook: F) -> Self
+    where
+        F: Fn(&Feedback<M>) + Send + Sync + 'static,
+    {
+        PlayerGen {
+            feedback: Some(Arc::new(Box::new(hook))),
+            ..self
+        }
+    }
+
+    /// Calls `hook` with the agent that played a finished match, and the result of that match.
+    /// Replaces any previous hook.
+    ///
+    /// Only agents that the `Match` hands back in `IntoAgents::into_agents` receive feedback.
+    /// Combined with `pooled`, this lets an agent improve over the course of a tournament.
+    pub fn on_agent_feedback<F>(self, hook: F) -> Self
+    where
+        M: IntoAgents,
+        F: Fn(&mut M::Agent, &Feedback<M>) + Send + Sync + 'static,
+    {
+        PlayerGen {
+            agent_feedback: Some(Arc::new(Box::new(hook))),
+            recover: Some(M::into_agents),
+            ..self
+        }
+    }
+
+    /// Returns the function that takes the agents out of a played match, if the agent is
+    /// needed after the match, for pooling or feedback.
+    pub(crate) fn recover_agents(&self) -> Option<RecoverAgents<M>> {
+        self.recover
+    }
+
+    /// Delivers the feedback of a finished match to the hooks of the player.
+    pub(crate) fn feedback(&self, agent: Option<&mut M::Agent>, feedback: &Feedback<M>) {
+        if let (Some(hook), Some(agent)) = (&self.agent_feedback, agent) {
+            hook(agent, feedback);
+        }
+        if let Some(hook) = &self.feedback {
+            hook(feedback);
+        }
+    }
+
+    /// Returns an agent to the pool after its match. Agents of players that are not pooled
+    /// are dropped.
+    pub(crate) fn return_agent(&self, agent: M::Agent) {
+        if let Some(pool) = &self.pool {
+            pool.give_back(agent);
+        }
+    }
+
+    /// Generates a new agent, regardless of pooling.
+    pub fn generate_agent(&self) -> M::Agent {
+        self.generator.create()
+    }
+
+    /// Returns an agent for a match: an idle agent from the pool if there is one,
+    /// a newly generated agent otherwise.
+    pub fn checkout_agent(&self) -> M::Agent {
+        self.pool
+            .as_ref()
+            .and_then(|pool| pool.checkout())
+            .unwrap_or_else(|| self.generate_agent())
+    }
+
+    /// Checks out an agent, and wraps it as the player with the given id.
+    pub(crate) fn instantiate(&self, id: PlayerId) -> Player<M> {
+        Player {
+            inner: self.checkout_agent(),
+            info: self.info.clone(),
+            id,
+            source: self.clone(),
+        }
+    }
+
+    /// Returns the data of the player with the given id.
+    pub(crate) fn data(&self, id: PlayerId) -> PlayerData {
+        PlayerData {
+            info: self.info.clone(),
+            id,
+        }
+    }
+}
+
+pub struct Player<M: Match> {
+    inner: M::Agent,
+    info: Arc<PlayerInfo>,
+    id: PlayerId,
+    source: PlayerGen<M>,
+}
+
+impl<M: Match> Player<M> {
+    pub fn info(&self) -> &PlayerInfo {
+        &self.info
+    }
+
+    /// Returns the generator the agent was created by.
+    pub(crate) fn source(&self) -> PlayerGen<M> {
+        self.source.clone()
+    }
+
+    pub fn id(&self) -> PlayerId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.info.key()
+    }
+
+    pub fn unpack(self) -> M::Agent {
+        self.inner
+    }
+
+    pub fn data(&self) -> PlayerData {
+        PlayerData {
+            info: self.info.clone(),
+            id: self.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerData {
+    info: Arc<PlayerInfo>,
+    id: PlayerId,
+}
+
+impl PlayerData {
+    pub fn info(&self) -> &PlayerInfo {
+        &self.info
+    }
+
+    pub fn id(&self) -> PlayerId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        self.info.key()
+    }
+}
+
+#[derive(Clone)]
+pub struct PlayerRepository<M: Match> {
+    players: HashMap<PlayerId, PlayerGen<M>>,
+    keys: HashMap<String, PlayerId>,
+    next_id: PlayerId,
+}
+
+impl<M: Match> Default for PlayerRepository<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Match> PlayerRepository<M> {
+    pub fn new() -> Self {
+        PlayerRepository {
+            players: HashMap::new(),
+            keys: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn ids(&self) -> Vec<PlayerId> {
+        let mut v = self.players.keys().cloned().collect::<Vec<_>>();
+        v.sort_unstable();
+        v
+    }
+
+    /// Adds a player and returns its id.
+    /// Ids are assigned in increasing order, and are never reused after a player is removed.
+    pub fn add_player(&mut self, player: PlayerGen<M>) -> PlayerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.players.insert(id, player);
+        id
+    }
+
+    /// Adds a player that can later be found by `key`, and returns its id.
+    /// The key is included in the player's data, and thereby in all match logs.
+    ///
+    /// Returns an error if another player already uses the key.
+    pub fn add_player_with_key(
+        &mut self,
+        key: impl Into<String>,
+        mut player: PlayerGen<M>,
+    ) -> Result<PlayerId, Error> {
+        let key = key.into();
+        if self.keys.contains_key(&key) {
+            return Err(Error::DuplicateKey(key));
+        }
+
+        player.info_mut().key = Some(key.clone());
+        let id = self.add_player(player);
+        self.keys.insert(key, id);
+        Ok(id)
+    }
+
+    /// Removes a player from the repository and returns it.
+    pub fn remove_player(&mut self, id: PlayerId) -> Result<PlayerGen<M>, Error> {
+        let player = self.players.remove(&id).ok_or(Error::PlayerNotFound(id))?;
+
+        if let Some(key) = &player.info.key {
+            self.keys.remove(key);
+        }
+        Ok(player)
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn contains(&self, id: PlayerId) -> bool {
+        self.players.contains_key(&id)
+    }
+
+    /// Returns the id of the player with the given key.
+    pub fn find_by_key(&self, key: &str) -> Option<PlayerId> {
+        self.keys.get(key).copied()
+    }
+
+    /// Returns the id of a player with the given name.
+    /// If several players share the name, the one with the smallest id is returned.
+    pub fn find_by_name(&self, name: &str) -> Option<PlayerId> {
+        self.players
+            .iter()
+            .filter(|(_, player)| player.info.name == name)
+            .map(|(id, _)| *id)
+            .min()
+    }
+
+    pub fn get_raw(&self, id: PlayerId) -> Result<PlayerGen<M>, Error> {
+        self.players
+            .get(&id)
+            .cloned()
+            .ok_or(Error::PlayerNotFound(id))
+    }
+
+    /// Returns the data of a player, as it appears in match results.
+    pub fn data(&self, id: PlayerId) -> Result<PlayerData, Error> {
+        self.players
+            .get(&id)
+            .map(|player| player.data(id))
+            .ok_or(Error::PlayerNotFound(id))
+    }
+
+    pub fn get_instance(&self, id: PlayerId) -> Result<Player<M>, Error> {
+        self.players
+            .get(&id)
+            .map(|player| player.instantiate(id))
+            .ok_or(Error::PlayerNotFound(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{Scored, Unplayed};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// An agent with a serial number, and weights that may be shared with other agents.
+    #[derive(Clone)]
+    struct Tuned {
+        serial: usize,
+        weights: Arc<Vec<f32>>,
+    }
+
+    /// Never played, only used to create agents.
+    struct Unplayable;
+
+    impl Match for Unplayable {
+        type Agent = Tuned;
+        type MatchResult = Scored;
+
+        fn new(_: Player<Self>, _: Player<Self>) -> Self {
+            Unplayable
+        }
+
+        fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+            unreachable!()
+        }
+    }
+
+    struct Factory {
+        serial: AtomicUsize,
+        weights: Arc<Vec<f32>>,
+    }
+
+    impl AgentFactory<Unplayable> for Factory {
+        fn create(&self) -> Tuned {
+            Tuned {
+                serial: self.serial.fetch_add(1, Ordering::SeqCst),
+                weights: self.weights.clone(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_factory() {
+        let weights = Arc::new(vec![0.5; 4]);
+        let player = PlayerGen::from_factory(Factory {
+            serial: AtomicUsize::new(0),
+            weights: weights.clone(),
+        });
+
+        // every agent is created by the factory, which is shared by clones of the player
+        let first = player.generate_agent();
+        let second = player.clone().checkout_agent();
+        assert_eq!((first.serial, second.serial), (0, 1));
+        assert!(Arc::ptr_eq(&first.weights, &weights));
+        assert!(Arc::ptr_eq(&second.weights, &weights));
+    }
+
+    #[test]
+    fn test_from_prototype() {
+        let weights = Arc::new(vec![0.5; 4]);
+        let player = PlayerGen::<Unplayable>::from_prototype(Tuned {
+            serial: 7,
+            weights: weights.clone(),
+        });
+
+        // agents are independent clones of the prototype
+        let mut first = player.generate_agent();
+        first.serial += 1;
+        let second = player.generate_agent();
+        assert_eq!((first.serial, second.serial), (8, 7));
+        assert!(Arc::ptr_eq(&second.weights, &weights));
+    }
+
+    #[test]
+    fn test_from_shared() {
+        let weights = Arc::new(vec![0.5; 4]);
+        let built = Arc::new(AtomicUsize::new(0));
+        let counter = built.clone();
+        let player = PlayerGen::<Unplayable>::from_shared(weights.clone(), move |weights| Tuned {
+            serial: counter.fetch_add(1, Ordering::SeqCst),
+            weights: Arc::new(weights.iter().map(|w| w * 2.0).collect()),
+        });
+        let clone = player.clone();
+
+        // every agent is built anew, but the resource is neither copied nor reloaded
+        let first = player.generate_agent();
+        let second = clone.generate_agent();
+        assert_eq!((first.serial, second.serial), (0, 1));
+        assert_eq!(*second.weights, [1.0; 4]);
+        assert_eq!(built.load(Ordering::SeqCst), 2);
+        assert_eq!(Arc::strong_count(&weights), 2);
+    }
+
+    #[test]
+    fn test_player_info() {
+        let depth = 12;
+        let player = PlayerGen::<Unplayed>::new(|| ())
+            .with_name(format!("minimax-d{}", depth))
+            .with_version("1.2")
+            .with_author("Ada")
+            .with_param("depth", depth)
+            .with_param("depth", 14)
+            .with_tag("search")
+            .with_tag("search");
+
+        let info = player.info();
+        assert_eq!(info.name(), "minimax-d12");
+        assert_eq!(info.key(), None);
+        assert_eq!(info.version(), Some("1.2"));
+        assert_eq!(info.author(), Some("Ada"));
+        // parameters are replaced, and tags are not duplicated
+        assert_eq!(info.param("depth"), Some("14"));
+        assert_eq!(info.params().len(), 1);
+        assert_eq!(info.tags(), ["search"]);
+        assert!(info.has_tag("search"));
+        assert!(!info.has_tag("random"));
+
+        // the metadata ends up in the data of the player
+        let mut players = PlayerRepository::new();
+        let id = players.add_player_with_key("minimax", player).unwrap();
+        let data = players.data(id).unwrap();
+        assert_eq!(data.name(), "minimax-d12");
+        assert_eq!(data.key(), Some("minimax"));
+        assert_eq!(data.info().version(), Some("1.2"));
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let mut players = PlayerRepository::<Unplayed>::new();
+        assert_eq!(players.add_player(PlayerGen::new(|| ())), 0);
+        assert_eq!(players.add_player(PlayerGen::new(|| ())), 1);
+
+        players.remove_player(1).unwrap();
+        assert!(!players.contains(1));
+        assert!(matches!(
+            players.remove_player(1),
+            Err(Error::PlayerNotFound(1))
+        ));
+        assert_eq!(players.add_player(PlayerGen::new(|| ())), 2);
+        assert_eq!(players.ids(), [0, 2]);
+        assert_eq!(players.len(), 2);
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut players = PlayerRepository::<Unplayed>::new();
+        let id = players
+            .add_player_with_key("minimax", PlayerGen::new(|| ()))
+            .unwrap();
+        assert!(matches!(
+            players.add_player_with_key("minimax", PlayerGen::new(|| ())),
+            Err(Error::DuplicateKey(key)) if key == "minimax"
+        ));
+        assert_eq!(players.len(), 1);
+        assert_eq!(players.find_by_key("minimax"), Some(id));
+        assert_eq!(players.find_by_key("random"), None);
+
+        // the key is released with its player
+        players.remove_player(id).unwrap();
+        assert_eq!(players.find_by_key("minimax"), None);
+        let id = players
+            .add_player_with_key("minimax", PlayerGen::new(|| ()))
+            .unwrap();
+        assert_eq!(id, 1);
+    }
+
+    #[test]
+    fn test_find_by_name() {
+        let mut players = PlayerRepository::<Unplayed>::new();
+        players.add_player(PlayerGen::new(|| ()).with_name("random"));
+        let first = players.add_player(PlayerGen::new(|| ()).with_name("minimax"));
+        let second = players.add_player(PlayerGen::new(|| ()).with_name("minimax"));
+
+        // the player with the smallest id is found
+        assert_eq!(players.find_by_name("minimax"), Some(first));
+        players.remove_player(first).unwrap();
+        assert_eq!(players.find_by_name("minimax"), Some(second));
+        assert_eq!(players.find_by_name("alphabeta"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_player() {
+        let mut players = PlayerRepository::<Unplayed>::new();
+        let id = players
+            .add_player_with_key(
+                "minimax",
+                PlayerGen::new(|| ())
+                    .with_name(format!("minimax-d{}", 12))
+                    .with_version("1.2")
+                    .with_param("depth", 12)
+                    .with_tag("search"),
+            )
+            .unwrap();
+        let data = players.data(id).unwrap();
+
+        // names built at runtime survive the round trip, as well as all metadata
+        let json = serde_json::to_string(&data).unwrap();
+        let restored: PlayerData = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.id(), id);
+        assert_eq!(restored.name(), "minimax-d12");
+        assert_eq!(restored.key(), Some("minimax"));
+        let info = restored.info();
+        assert_eq!(info.version(), Some("1.2"));
+        assert_eq!(info.author(), None);
+        assert_eq!(info.params(), data.info().params());
+        assert_eq!(info.tags(), ["search"]);
+
+        // unset metadata is omitted, and a static name deserializes as well
+        let info = PlayerInfo::new(Cow::Borrowed("random"));
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(json, r#"{"name":"random"}"#);
+        let restored: PlayerInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.name(), "random");
+        assert!(restored.params().is_empty());
+    }
+
+    #[test]
+    fn test_default_name() {
+        let player = PlayerGen::<Unplayed>::new(|| ());
+        assert_eq!(player.info().name(), "()");
+    }
+}
@@ -0,0 +1,139 @@
+use crate::game::Match;
+use std::sync::Mutex;
+
+type ResetHook<A> = Box<dyn Fn(&mut A) + Send + Sync>;
+
+/// Idle agents of a pooled player, that are reused across matches instead of being
+/// generated anew for every match.
+///
+/// Agents are checked out for a match and returned afterwards. If all agents are in use, for
+/// example because the player takes part in several concurrent matches, a new agent is
+/// generated. The pool therefore grows up to the number of concurrent matches of the player.
+pub struct AgentPool<M: Match> {
+    idle: Mutex<Vec<M::Agent>>,
+    reset: Option<ResetHook<M::Agent>>,
+}
+
+impl<M: Match> AgentPool<M> {
+    pub(crate) fn new() -> Self {
+        AgentPool {
+            idle: Mutex::new(Vec::new()),
+            reset: None,
+        }
+    }
+
+    pub(crate) fn with_reset<F>(reset: F) -> Self
+    where
+        F: Fn(&mut M::Agent) + Send + Sync + 'static,
+    {
+        AgentPool {
+            idle: Mutex::new(Vec::new()),
+            reset: Some(Box::new(reset)),
+        }
+    }
+
+    /// Takes an idle agent out of the pool, if there is one.
+    pub fn checkout(&self) -> Option<M::Agent> {
+        self.idle.lock().expect("Agent pool poisoned").pop()
+    }
+
+    /// Resets an agent with the reset hook, and returns it to the pool.
+    pub fn give_back(&self, mut agent: M::Agent) {
+        if let Some(reset) = &self.reset {
+            reset(&mut agent);
+        }
+        self.idle.lock().expect("Agent pool poisoned").push(agent);
+    }
+
+    /// Returns the number of idle agents.
+    pub fn idle(&self) -> usize {
+        self.idle.lock().expect("Agent pool poisoned").len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executor::InlineExecutor;
+    use crate::game::{IntoAgents, Match, MatchResult, Outcome};
+    use crate::player::{Player, PlayerGen, PlayerRepository};
+    use crate::scheduling::RoundRobbinScheduler;
+    use crate::scoring::Standings;
+    use crate::tournaments::Tournament;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counter {
+        games: usize,
+        resets: usize,
+    }
+
+    /// Player 1 always wins. Both agents count their games.
+    struct Rally(Counter, Counter);
+
+    #[derive(Debug)]
+    struct RallyResult;
+
+    impl MatchResult for RallyResult {
+        fn outcome(&self) -> Outcome {
+            Outcome::WinP1
+        }
+    }
+
+    impl Match for Rally {
+        type Agent = Counter;
+        type MatchResult = RallyResult;
+
+        fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+            Rally(player1.unpack(), player2.unpack())
+        }
+
+        fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+            self.0.games += 1;
+            self.1.games += 1;
+            Ok(RallyResult)
+        }
+    }
+
+    impl IntoAgents for Rally {
+        fn into_agents(self) -> (Option<Counter>, Option<Counter>) {
+            (Some(self.0), Some(self.1))
+        }
+    }
+
+    /// Counts the agents it creates.
+    fn counted(created: &Arc<AtomicUsize>) -> PlayerGen<Rally> {
+        let created = created.clone();
+        PlayerGen::new(move || {
+            created.fetch_add(1, Ordering::SeqCst);
+            Counter::default()
+        })
+    }
+
+    #[test]
+    fn test_pooled_agents_are_reused() {
+        let pooled = Arc::new(AtomicUsize::new(0));
+        let fresh = Arc::new(AtomicUsize::new(0));
+
+        let mut players = PlayerRepository::new();
+        let id = players.add_player(counted(&pooled).pooled_with_reset(|agent| agent.resets += 1));
+        players.add_player(counted(&fresh));
+        players.add_player(counted(&fresh));
+
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        tournament.init();
+        assert_eq!(tournament.by_ref().count(), 3);
+
+        // every player plays twice, but the pooled player uses a single agent
+        assert_eq!(pooled.load(Ordering::SeqCst), 1);
+        assert_eq!(fresh.load(Ordering::SeqCst), 4);
+
+        let player = tournament.players().get_raw(id).unwrap();
+        let pool = player.pool().unwrap();
+        assert_eq!(pool.idle(), 1);
+        let agent = pool.checkout().unwrap();
+        assert_eq!((agent.games, agent.resets), (2, 2));
+    }
+}
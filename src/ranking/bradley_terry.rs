@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Error};
+use crate::error::Error;
 use ndarray::{Array, Array1, Array2, Axis};
 
 fn log_likelihood(win_matrix: &Array2<f64>, params: &Array1<f64>) -> f64 {
@@ -24,7 +24,9 @@ fn ll_gradient(win_matrix: &Array2<f64>, params: &Array1<f64>) -> Result<Array1<
         if sum != 0.0 {
             gradient[i] = w[i] / sum;
         } else {
-            return Err(anyhow!("gradient calculation: division by zero"));
+            return Err(Error::Numerical(
+                "gradient calculation: division by zero".into(),
+            ));
         }
     }
 
@@ -36,7 +38,10 @@ fn normalize(v: Array1<f64>) -> Array1<f64> {
     v.map(|x| x / sum)
 }
 
-struct BradleyTerry {
+/// Maximum likelihood estimate of the Bradley-Terry model, where player `i` beats player `j`
+/// with probability `p_i / (p_i + p_j)`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BradleyTerry {
     win_matrix: Array2<f64>,
     params: Option<Array1<f64>>,
     is_computed: bool,
@@ -46,7 +51,7 @@ struct BradleyTerry {
 impl BradleyTerry {
     /// Creates a new Bradley-Terry model with the given win matrix.
     /// `eps` is the convergence threshold, and is set to 1e-7 by default.
-    fn new(win_matrix: Array2<f64>) -> Self {
+    pub fn new(win_matrix: Array2<f64>) -> Self {
         Self {
             win_matrix,
             params: None,
@@ -56,18 +61,21 @@ impl BradleyTerry {
     }
 
     /// Sets the convergence threshold.
-    fn with_eps(self, eps: f64) -> Self {
+    pub fn with_eps(self, eps: f64) -> Self {
         Self { eps, ..self }
     }
 
     /// Computes the parameters.
-    fn compute_params(&mut self) {
+    ///
+    /// Returns an error if a player has neither won nor lost a game, as its parameter is
+    /// undetermined.
+    pub fn compute_params(&mut self) -> Result<(), Error> {
         let mut params = Array::from_vec(vec![1.0; self.win_matrix.len_of(Axis(0))]);
 
         const MAX_ITER: usize = 200;
 
         for i in 0..MAX_ITER {
-            let grad = ll_gradient(&self.win_matrix, &params).unwrap();
+            let grad = ll_gradient(&self.win_matrix, &params)?;
             let new_params = normalize(&params + &grad);
 
             if i % 10 == 0 {
@@ -84,19 +92,20 @@ impl BradleyTerry {
 
         self.is_computed = true;
         self.params = Some(params);
+        Ok(())
     }
 
     /// Returns the estimated parameters.
     /// If the parameters have not been computed yet, they will be computed.
-    fn params(&mut self) -> Array1<f64> {
+    pub fn params(&mut self) -> Result<Array1<f64>, Error> {
         if !self.is_computed {
-            self.compute_params();
+            self.compute_params()?;
         }
-        self.params.clone().unwrap()
+        Ok(self.params.clone().unwrap())
     }
 
     /// Updates the win matrix.
-    fn update(&mut self, win_matrix: Array2<f64>) {
+    pub fn update(&mut self, win_matrix: Array2<f64>) {
         self.win_matrix = win_matrix;
         self.is_computed = false;
     }
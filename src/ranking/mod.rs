@@ -1,8 +1,72 @@
+use crate::error::Error;
 use crate::player::PlayerId;
+use crate::scoring::Crosstable;
+use ndarray::Array2;
 
 mod bradley_terry;
 
+pub use bradley_terry::BradleyTerry;
+
 pub trait RankingPolicy {
     /// Returns the current ranking of players based on the scores table.
     fn rank_players(&mut self) -> Vec<PlayerId>;
 }
+
+/// Estimates an Elo-scale performance rating for every player of the crosstable, in the order
+/// of `Crosstable::players`. The ratings are centered around 1500.
+///
+/// The ratings are derived from a Bradley-Terry fit of the head-to-head points. Every pair that
+/// met is credited one virtual draw, so that players without wins or losses get finite ratings.
+/// Like the crosstable, the ratings only reflect played matches, and ignore forfeits.
+pub fn performance_ratings(crosstable: &Crosstable) -> Result<Vec<f64>, Error> {
+    let n = crosstable.players().len();
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut wins = Array2::zeros((n, n));
+    for i in 0..n {
+        for j in 0..n {
+            if crosstable.games(i, j) > 0 {
+                wins[[i, j]] = crosstable.points(i, j) + 0.5;
+            }
+        }
+    }
+
+    let params = BradleyTerry::new(wins).params()?;
+    let ratings = params.map(|p| 400.0 * p.log10());
+    let mean = ratings.sum() / n as f64;
+    Ok(ratings.iter().map(|r| r - mean + 1500.0).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::LabelledOutcome;
+    use crate::test_util::players;
+
+    #[test]
+    fn test_performance_ratings() {
+        let players = players(3);
+        let win = |winner: usize, loser: usize| LabelledOutcome::Win {
+            winner: players[winner].clone(),
+            loser: players[loser].clone(),
+        };
+        let outcomes = [
+            win(0, 1),
+            win(0, 2),
+            LabelledOutcome::Draw(players[1].clone(), players[2].clone()),
+        ];
+
+        let ratings = performance_ratings(&Crosstable::from_outcomes(&outcomes)).unwrap();
+        assert_eq!(ratings.len(), 3);
+        assert!((ratings.iter().sum::<f64>() / 3.0 - 1500.0).abs() < 1e-6);
+        // the players with equal results are rated equally, and below the winner
+        assert!(ratings[0] > ratings[1]);
+        assert!((ratings[1] - ratings[2]).abs() < 1e-3);
+
+        assert!(performance_ratings(&Crosstable::from_outcomes(&[]))
+            .unwrap()
+            .is_empty());
+    }
+}
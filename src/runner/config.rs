@@ -0,0 +1,304 @@
+use crate::error::Error;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The configuration of a tournament run, as read from a TOML or JSON file.
+///
+/// ```toml
+/// players = [
+///     "random",
+///     { agent = "minimax", name = "minimax-d12", params = { depth = 12 }, tags = ["search"] },
+/// ]
+/// threads = 4
+/// games_per_pairing = 2
+/// seed = 42
+/// ranking = "performance"
+///
+/// [scheduler]
+/// type = "round_robin"
+/// r = 1
+///
+/// [scoring]
+/// type = "standings"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub players: Vec<PlayerConfig>,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+    /// How often every pairing is played, alternating sides. Must be at least 1.
+    #[serde(default = "default_games_per_pairing")]
+    pub games_per_pairing: usize,
+    /// Seed used to shuffle the player order before scheduling, and to seed the matches and
+    /// randomised schedulers, so that runs can be reproduced. If omitted, players are scheduled
+    /// in the order they are listed, and matches are seeded randomly.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_threads() -> usize {
+    1
+}
+
+fn default_games_per_pairing() -> usize {
+    1
+}
+
+/// A participant, either given by the name of an agent in the `AgentRegistry`, or as a table
+/// that additionally overrides the name and metadata of the agent.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum PlayerConfig {
+    Agent(String),
+    Custom {
+        agent: String,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        version: Option<String>,
+        #[serde(default)]
+        author: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        params: BTreeMap<String, serde_json::Value>,
+    },
+}
+
+impl PlayerConfig {
+    /// The name of the agent in the `AgentRegistry`.
+    pub fn agent(&self) -> &str {
+        match self {
+            PlayerConfig::Agent(agent) => agent,
+            PlayerConfig::Custom { agent, .. } => agent,
+        }
+    }
+
+    /// The display name of the player, which defaults to the name of the agent.
+    pub fn name(&self) -> &str {
+        match self {
+            PlayerConfig::Custom {
+                name: Some(name), ..
+            } => name,
+            _ => self.agent(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SchedulerConfig {
+    /// See `RoundRobbinScheduler::with_r`, `RoundRobbinScheduler::with_cycles` and
+    /// `RoundRobbinScheduler::with_berger_tables`.
+    RoundRobin {
+        #[serde(default = "default_r")]
+        r: usize,
+        #[serde(default = "default_cycles")]
+        cycles: usize,
+        #[serde(default)]
+        berger: bool,
+    },
+    /// The challenger is the name of one of the players.
+    Gauntlet { challenger: String },
+    /// Players are seeded in the order they are listed. See `KnockoutScheduler`.
+    Knockout,
+    /// A round robin in each of `groups` groups, after which the top `advance` players of each
+    /// group play a knockout bracket. See `draw_groups` and `GroupScheduler::with_playoffs`.
+    Groups {
+        groups: usize,
+        advance: usize,
+        #[serde(default)]
+        snake: bool,
+    },
+    /// A continuous ladder, stopped after `matches` pairings. See `LadderScheduler`.
+    Ladder { matches: usize },
+    /// A Swiss system of `rounds` rounds. Players are seeded in the order they are listed.
+    /// See `SwissScheduler`.
+    Swiss { rounds: usize },
+    /// Pairs by expected information gain until the ranking has the given `confidence`, or
+    /// after at most `matches` pairings. See `ActiveScheduler`.
+    Active {
+        #[serde(default = "default_confidence")]
+        confidence: f64,
+        #[serde(default)]
+        matches: Option<usize>,
+    },
+}
+
+fn default_r() -> usize {
+    1
+}
+
+fn default_cycles() -> usize {
+    1
+}
+
+fn default_confidence() -> f64 {
+    0.95
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        SchedulerConfig::RoundRobin {
+            r: default_r(),
+            cycles: default_cycles(),
+            berger: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ScoringConfig {
+    /// See `DefaultScoring`.
+    Default,
+    /// See `Standings`.
+    #[default]
+    Standings,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingConfig {
+    /// Rank by the ranking of the scoring system.
+    #[default]
+    Scoring,
+    /// Rank by performance rating.
+    Performance,
+}
+
+impl Config {
+    /// Reads the configuration from a file.
+    /// Files ending in `.json` are parsed as JSON, everything else as TOML.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Cannot read config {}: {}", path.display(), e)))?;
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Config::from_json(&content)
+        } else {
+            Config::from_toml(&content)
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, Error> {
+        toml::from_str::<Config>(content)?.validate()
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, Error> {
+        serde_json::from_str::<Config>(content)?.validate()
+    }
+
+    /// Rejects values that are well-formed, but cannot be run.
+    fn validate(self) -> Result<Self, Error> {
+        if self.games_per_pairing == 0 {
+            return Err(Error::Config("games_per_pairing must be at least 1".into()));
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml(
+            r#"
+            players = [
+                "random",
+                { agent = "minimax", name = "minimax-d12", params = { depth = 12 }, tags = ["search"] },
+            ]
+            threads = 4
+            seed = 42
+            ranking = "performance"
+
+            [scheduler]
+            type = "swiss"
+            rounds = 5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.players.len(), 2);
+        assert_eq!(config.players[0].agent(), "random");
+        assert_eq!(config.players[0].name(), "random");
+        assert_eq!(config.players[1].agent(), "minimax");
+        assert_eq!(config.players[1].name(), "minimax-d12");
+        match &config.players[1] {
+            PlayerConfig::Custom { params, tags, .. } => {
+                assert_eq!(params["depth"], 12);
+                assert_eq!(tags, &["search"]);
+            }
+            PlayerConfig::Agent(_) => panic!("expected a custom player"),
+        }
+
+        assert_eq!(config.threads, 4);
+        assert_eq!(config.games_per_pairing, 1);
+        assert_eq!(config.seed, Some(42));
+        assert!(matches!(config.ranking, RankingConfig::Performance));
+        assert!(matches!(
+            config.scheduler,
+            SchedulerConfig::Swiss { rounds: 5 }
+        ));
+        assert!(matches!(config.scoring, ScoringConfig::Standings));
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::from_json(r#"{ "players": ["a", "b"] }"#).unwrap();
+        assert_eq!(config.threads, 1);
+        assert_eq!(config.seed, None);
+        assert!(matches!(
+            config.scheduler,
+            SchedulerConfig::RoundRobin {
+                r: 1,
+                cycles: 1,
+                berger: false
+            }
+        ));
+    }
+
+    #[test]
+    fn test_games_per_pairing() {
+        let config = Config::from_toml("players = []\ngames_per_pairing = 2").unwrap();
+        assert_eq!(config.games_per_pairing, 2);
+        assert!(matches!(
+            Config::from_toml("players = []\ngames_per_pairing = 0"),
+            Err(Error::Config(_))
+        ));
+        assert!(matches!(
+            Config::from_json(r#"{ "players": [], "games_per_pairing": 0 }"#),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        assert!(matches!(
+            Config::from_toml("players = []\nthread = 4"),
+            Err(Error::Serialization(_))
+        ));
+        assert!(matches!(
+            Config::from_json(
+                r#"{ "players": [], "scheduler": { "type": "ladder", "matches": 10, "r": 2 } }"#
+            ),
+            Err(Error::Serialization(_))
+        ));
+        // a misspelled field of a player is not silently ignored
+        assert!(matches!(
+            Config::from_toml(r#"players = [{ agent = "random", nmae = "other" }]"#),
+            Err(Error::Serialization(_))
+        ));
+    }
+}
@@ -0,0 +1,466 @@
+//! Runs a tournament from a configuration file, so that embedding crates only need to provide
+//! their agents.
+//!
+//! ```ignore
+//! fn main() -> Result<(), Error> {
+//!     let mut registry = AgentRegistry::<MyGame>::new();
+//!     registry.register("random", PlayerGen::new(RandomAgent::new));
+//!     run_main(&registry)
+//! }
+//! ```
+
+mod config;
+
+pub use config::*;
+
+use crate::error::Error;
+use crate::export::Leaderboard;
+use crate::game::{LabelledFault, LabelledOutcome, Match};
+use crate::history::MatchHistory;
+use crate::player::{PlayerGen, PlayerId, PlayerRepository};
+use crate::ranking::RankingPolicy;
+use crate::scheduling::{
+    draw_groups, ActiveScheduler, Draw, GauntletScheduler, GroupScheduler, KnockoutScheduler,
+    LadderScheduler, RoundRobbinScheduler, Scheduler, SchedulerExt, SwissScheduler,
+};
+use crate::scoring::{Crosstable, DefaultScoring, ScoringSystem, Standings};
+use crate::tournaments::{ProgressReporter, Tournament, TournamentEvent};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// The agents a runner can choose from, by name.
+pub struct AgentRegistry<M: Match> {
+    agents: HashMap<String, PlayerGen<M>>,
+}
+
+impl<M: Match> Default for AgentRegistry<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Match> AgentRegistry<M> {
+    pub fn new() -> Self {
+        AgentRegistry {
+            agents: HashMap::new(),
+        }
+    }
+
+    /// Registers an agent under the given name, replacing any previous agent of that name.
+    pub fn register(&mut self, name: impl Into<String>, player: PlayerGen<M>) -> &mut Self {
+        self.agents.insert(name.into(), player);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Result<PlayerGen<M>, Error> {
+        self.agents
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::Config(format!("Agent {} is not registered", name)))
+    }
+
+    /// Returns the names of all registered agents, sorted alphabetically.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.agents.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Entry point for a runner binary. Reads the path of the config file from the first
+/// command line argument, runs the tournament and prints the results to stdout.
+pub fn run_main<M: Match + 'static>(registry: &AgentRegistry<M>) -> Result<(), Error> {
+    let path = std::env::args().nth(1).ok_or_else(|| {
+        Error::Config(format!(
+            "Usage: <config.toml|config.json>\nRegistered agents: {}",
+            registry.names().join(", ")
+        ))
+    })?;
+
+    let config = Config::from_file(path)?;
+    run(&config, registry, std::io::stdout())?;
+    Ok(())
+}
+
+/// Runs the tournament described by `config`. Progress is reported on stderr, and failed
+/// matches and the final standings are written to `out`. Returns the history of all
+/// successfully played matches.
+pub fn run<M, W>(
+    config: &Config,
+    registry: &AgentRegistry<M>,
+    mut out: W,
+) -> Result<MatchHistory<M::MatchResult>, Error>
+where
+    M: Match + 'static,
+    W: Write,
+{
+    if config.players.len() < 2 {
+        return Err(Error::Config("At least two players are required".into()));
+    }
+
+    let mut players = PlayerRepository::new();
+    let ids = config
+        .players
+        .iter()
+        .map(|player| Ok(players.add_player(player_gen(player, registry)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut order = ids.clone();
+    if let Some(seed) = config.seed {
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+    }
+
+    let scheduler: Box<dyn Scheduler> = match &config.scheduler {
+        SchedulerConfig::RoundRobin { r, cycles, berger } => {
+            let mut scheduler = RoundRobbinScheduler::new(order)
+                .with_r(*r)?
+                .with_cycles(*cycles)?;
+            if *berger {
+                scheduler = scheduler.with_berger_tables();
+            }
+            Box::new(scheduler)
+        }
+        SchedulerConfig::Gauntlet { challenger } => {
+            let challenger = config
+                .players
+                .iter()
+                .position(|player| player.name() == challenger)
+                .ok_or_else(|| {
+                    Error::Config(format!("Challenger {} is not a player", challenger))
+                })?;
+            Box::new(GauntletScheduler::new(ids[challenger], order))
+        }
+        SchedulerConfig::Knockout => Box::new(KnockoutScheduler::new(order)),
+        SchedulerConfig::Groups {
+            groups,
+            advance,
+            snake,
+        } => {
+            if *groups == 0 || *groups > order.len() {
+                return Err(Error::Config(format!(
+                    "Cannot draw {} groups from {} players",
+                    groups,
+                    order.len()
+                )));
+            }
+            let draw = if *snake { Draw::Snake } else { Draw::Seeded };
+            let groups = draw_groups(order, *groups, draw)?;
+            Box::new(GroupScheduler::new(groups)?.with_playoffs(*advance))
+        }
+        SchedulerConfig::Swiss { rounds } => Box::new(SwissScheduler::new(order, *rounds)),
+        SchedulerConfig::Ladder { matches } => {
+            let mut ladder = LadderScheduler::new(order);
+            if let Some(seed) = config.seed {
+                ladder = ladder.with_seed(seed);
+            }
+            Box::new(ladder.limit(*matches))
+        }
+        SchedulerConfig::Active {
+            confidence,
+            matches,
+        } => {
+            let active = ActiveScheduler::new(order).with_confidence(*confidence)?;
+            match matches {
+                Some(matches) => Box::new(active.limit(*matches)),
+                None => Box::new(active),
+            }
+        }
+    };
+
+    let scheduler = Repeated::new(scheduler, config.games_per_pairing);
+
+    let Played {
+        history,
+        ranking,
+        faults,
+    } = match config.scoring {
+        ScoringConfig::Default => play(
+            config,
+            scheduler,
+            DefaultScoring::new(players.ids()),
+            players,
+            &mut out,
+        )?,
+        ScoringConfig::Standings => play(config, scheduler, Standings::new(), players, &mut out)?,
+    };
+
+    let mut standings = Standings::from_outcomes(history.outcomes());
+    for fault in &faults {
+        standings.record_fault(fault);
+    }
+    let crosstable = Crosstable::from_outcomes(history.outcomes());
+
+    let leaderboard = Leaderboard::new(&standings, &crosstable)?;
+    let leaderboard = match config.ranking {
+        RankingConfig::Scoring => leaderboard.ranked_by(&ranking),
+        RankingConfig::Performance => leaderboard.ranked_by_performance(),
+    };
+
+    writeln!(out)?;
+    writeln!(out, "{}", leaderboard)?;
+    writeln!(out, "{}", crosstable)?;
+
+    Ok(history)
+}
+
+/// Looks up the agent of a player, and applies the overrides of the config.
+fn player_gen<M: Match>(
+    config: &PlayerConfig,
+    registry: &AgentRegistry<M>,
+) -> Result<PlayerGen<M>, Error> {
+    let player = registry.get(config.agent())?;
+
+    match config {
+        PlayerConfig::Agent(_) => Ok(player),
+        PlayerConfig::Custom {
+            name,
+            version,
+            author,
+            tags,
+            params,
+            ..
+        } => {
+            let mut player = match name {
+                Some(name) => player.with_name(name.clone()),
+                None => player,
+            };
+            if let Some(version) = version {
+                player = player.with_version(version);
+            }
+            if let Some(author) = author {
+                player = player.with_author(author);
+            }
+            for tag in tags {
+                player = player.with_tag(tag);
+            }
+            for (key, value) in params {
+                player = match value {
+                    serde_json::Value::String(value) => player.with_param(key, value),
+                    value => player.with_param(key, value),
+                };
+            }
+            Ok(player)
+        }
+    }
+}
+
+/// The matches of a tournament, and the final ranking of its scoring system.
+struct Played<R> {
+    history: MatchHistory<R>,
+    ranking: Vec<PlayerId>,
+    faults: Vec<LabelledFault>,
+}
+
+fn play<M, P, S, W>(
+    config: &Config,
+    scheduler: P,
+    scoring: S,
+    players: PlayerRepository<M>,
+    mut out: W,
+) -> Result<Played<M::MatchResult>, Error>
+where
+    M: Match + 'static,
+    P: Scheduler,
+    S: ScoringSystem<M> + RankingPolicy,
+    W: Write,
+{
+    let mut tournament = Tournament::new(scheduler, scoring, players);
+    tournament.set_threads(config.threads.max(1));
+    if let Some(seed) = config.seed {
+        tournament.set_seed(seed);
+    }
+
+    // faults are only reported as events, as they have no match result for the history
+    let faults = Arc::new(Mutex::new(Vec::new()));
+    let collected = faults.clone();
+    tournament.add_observer(move |event: &TournamentEvent| {
+        if let TournamentEvent::MatchFinished {
+            fault: Some(fault), ..
+        } = event
+        {
+            collected.lock().unwrap().push(fault.clone());
+        }
+    });
+    tournament.add_observer(ProgressReporter::stderr());
+    tournament.init();
+
+    let mut history = MatchHistory::new();
+    for result in tournament.by_ref() {
+        match result {
+            Ok((outcome, result)) => history.push(outcome, result),
+            Err(e) => writeln!(out, "Match failed: {}", e)?,
+        }
+    }
+
+    let ranking = tournament.into_scoring().rank_players();
+    let faults = std::mem::take(&mut *faults.lock().unwrap());
+    Ok(Played {
+        history,
+        ranking,
+        faults,
+    })
+}
+
+/// Plays every pairing of the inner scheduler `games` times, alternating sides.
+struct Repeated<P: Scheduler> {
+    inner: P,
+    games: usize,
+    current: Option<(PlayerId, PlayerId)>,
+    played: usize,
+}
+
+impl<P: Scheduler> Repeated<P> {
+    fn new(inner: P, games: usize) -> Self {
+        Repeated {
+            inner,
+            games,
+            current: None,
+            played: 0,
+        }
+    }
+
+    fn repeat(&mut self) -> Option<(PlayerId, PlayerId)> {
+        let (p1, p2) = self.current?;
+        if self.played >= self.games {
+            return None;
+        }
+
+        self.played += 1;
+        if self.played % 2 == 1 {
+            Some((p1, p2))
+        } else {
+            Some((p2, p1))
+        }
+    }
+
+    fn start(&mut self, pair: Option<(PlayerId, PlayerId)>) -> Option<(PlayerId, PlayerId)> {
+        self.current = pair;
+        self.played = 0;
+        self.repeat()
+    }
+}
+
+impl<P: Scheduler> Scheduler for Repeated<P> {
+    fn init(&mut self) {
+        self.inner.init();
+    }
+
+    fn start_round(&mut self) {
+        self.inner.start_round();
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.inner.round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        self.inner.report(outcome);
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.inner.report_fault(fault);
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        self.inner.report_failure(player1, player2);
+    }
+
+    fn add_player(&mut self, player: PlayerId) {
+        self.inner.add_player(player);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        self.inner.remove_player(player);
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        let current = match self.current {
+            Some(_) => self.games - self.played,
+            None => 0,
+        };
+        // the inner scheduler may be unbounded, e.g. a ladder
+        let (lower, upper) = self.inner.remaining();
+        (
+            lower.saturating_mul(self.games).saturating_add(current),
+            upper.and_then(|upper| upper.checked_mul(self.games)?.checked_add(current)),
+        )
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        match self.repeat() {
+            Some(pair) => Some(pair),
+            None => {
+                let pair = self.inner.get();
+                self.start(pair)
+            }
+        }
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        match self.repeat() {
+            Some(pair) => Ok(Some(pair)),
+            None => {
+                let pair = self.inner.try_get()?;
+                Ok(self.start(pair))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::Ids;
+
+    #[test]
+    fn test_more_groups_than_players() {
+        let mut registry = AgentRegistry::<Ids>::new();
+        registry.register("ids", PlayerGen::new(|| ()));
+        let config = Config::from_toml(
+            r#"
+            players = ["ids", "ids"]
+
+            [scheduler]
+            type = "groups"
+            groups = 3
+            advance = 1
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            run(&config, &registry, Vec::new()),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_repeated() {
+        let gauntlet = GauntletScheduler::new(0, vec![1, 2]);
+        let mut repeated = Repeated::new(gauntlet, 3);
+        assert_eq!(repeated.remaining(), (6, Some(6)));
+
+        assert_eq!(repeated.get(), Some((0, 1)));
+        assert_eq!(repeated.remaining(), (5, Some(5)));
+
+        // every pairing is played three times, alternating sides
+        let rest = std::iter::from_fn(|| repeated.try_get().unwrap()).collect::<Vec<_>>();
+        assert_eq!(rest, [(1, 0), (0, 1), (2, 0), (0, 2), (2, 0)]);
+        assert_eq!(repeated.remaining(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_repeated_ladder() {
+        let ladder = LadderScheduler::new(vec![0, 1, 2]).with_seed(3);
+        let mut repeated = Repeated::new(ladder, 2);
+        assert_eq!(repeated.remaining(), (usize::MAX, None));
+
+        let (p1, p2) = repeated.get().unwrap();
+        assert_eq!(repeated.remaining(), (usize::MAX, None));
+        assert_eq!(repeated.get(), Some((p2, p1)));
+    }
+}
@@ -0,0 +1,377 @@
+/*------------------------------------------------------------------------------------------------*/
+/*--------------------------------- Active Learning Scheduler ------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::PlayerId;
+use crate::ranking::BradleyTerry;
+use crate::scheduling::Scheduler;
+use ndarray::{s, Array2};
+use std::collections::VecDeque;
+
+/// Pairs the players whose order is the most uncertain, until the ranking is confident.
+///
+/// After every round, the strengths of the players are estimated by a Bradley-Terry fit of all
+/// results, and their uncertainty by the Fisher information of the fit. The next round pairs
+/// the players by the expected information gain of their match: the probability that their
+/// estimated order is wrong, times the variance of the result, weighted by the uncertainty of
+/// both strengths. This concentrates the matches on players of similar strength whose order
+/// is not settled yet, and never pairs players that are ordered with confidence already.
+///
+/// The tournament ends once every two players that are adjacent in the ranking are ordered
+/// with the requested confidence. Combine with `SchedulerExt::limit` to bound the number of
+/// matches as well.
+pub struct ActiveScheduler {
+    players: Vec<PlayerId>,
+    /// Whether a player is still paired. Retired players stay in the fit.
+    active: Vec<bool>,
+    /// The points of player i against player j.
+    wins: Array2<f64>,
+    /// The number of pairings of every player, and of those as Player 1.
+    scheduled: Vec<usize>,
+    as_player1: Vec<usize>,
+    /// The estimated log-strength and its Fisher information of every player.
+    strengths: Vec<f64>,
+    information: Vec<f64>,
+    confidence: f64,
+    batch: Option<usize>,
+    pairs: VecDeque<(PlayerId, PlayerId)>,
+}
+
+/// The probability that player i beats player j, given their log-strengths.
+fn win_probability(si: f64, sj: f64) -> f64 {
+    1.0 / (1.0 + (sj - si).exp())
+}
+
+impl ActiveScheduler {
+    pub fn new(players: Vec<PlayerId>) -> Self {
+        let n = players.len();
+        ActiveScheduler {
+            active: vec![true; n],
+            wins: Array2::zeros((n, n)),
+            scheduled: vec![0; n],
+            as_player1: vec![0; n],
+            strengths: vec![0.0; n],
+            information: vec![0.0; n],
+            players,
+            confidence: 0.95,
+            batch: None,
+            pairs: VecDeque::new(),
+        }
+    }
+
+    /// Sets the probability with which every two adjacent players must be ordered correctly,
+    /// before the tournament ends. The default is 0.95.
+    ///
+    /// Returns an error if the confidence is less than 0.5, which any order has, or not less
+    /// than 1, which no order ever reaches.
+    pub fn with_confidence(self, confidence: f64) -> Result<Self, Error> {
+        if !(0.5..1.0).contains(&confidence) {
+            return Err(Error::Scheduler(format!(
+                "confidence must be at least 0.5 and less than 1, got {}",
+                confidence
+            )));
+        }
+        Ok(ActiveScheduler { confidence, ..self })
+    }
+
+    /// Sets the number of pairings per round. Smaller rounds adapt to the results sooner, larger
+    /// rounds play more matches at once. By default, every player is paired once per round.
+    pub fn with_batch(self, batch: usize) -> Self {
+        ActiveScheduler {
+            batch: Some(batch.max(1)),
+            ..self
+        }
+    }
+
+    /// Returns the players by estimated strength, best first.
+    pub fn ranking(&self) -> Vec<PlayerId> {
+        self.order(false)
+            .into_iter()
+            .map(|i| self.players[i])
+            .collect()
+    }
+
+    /// Returns an Elo-scale rating of every player, best first.
+    pub fn ratings(&self) -> Vec<(PlayerId, f64)> {
+        // log10(e) * 400
+        let scale = 400.0 / std::f64::consts::LN_10;
+        self.order(false)
+            .into_iter()
+            .map(|i| (self.players[i], 1500.0 + scale * self.strengths[i]))
+            .collect()
+    }
+
+    /// Whether every two adjacent active players are ordered with the requested confidence.
+    pub fn is_confident(&self) -> bool {
+        self.order(true)
+            .windows(2)
+            .all(|pair| self.error_probability(pair[0], pair[1]) < 1.0 - self.confidence)
+    }
+
+    /// The indices of the players by estimated strength, best first.
+    fn order(&self, active_only: bool) -> Vec<usize> {
+        let mut order = (0..self.players.len())
+            .filter(|&i| self.active[i] || !active_only)
+            .collect::<Vec<_>>();
+        order.sort_by(|&i, &j| self.strengths[j].total_cmp(&self.strengths[i]));
+        order
+    }
+
+    fn index(&self, player: PlayerId) -> Option<usize> {
+        self.players.iter().position(|&p| p == player)
+    }
+
+    /// The approximate probability that the estimated order of two players is wrong, from the
+    /// normal distribution of the difference of their log-strengths.
+    fn error_probability(&self, i: usize, j: usize) -> f64 {
+        let deviation = (1.0 / self.information[i] + 1.0 / self.information[j]).sqrt();
+        let z = (self.strengths[i] - self.strengths[j]).abs() / deviation;
+        // a logistic approximation of the normal distribution function at -z
+        1.0 / (1.0 + (1.702 * z).exp())
+    }
+
+    /// Estimates the strengths of the players, and their Fisher information.
+    fn fit(&mut self) {
+        let n = self.players.len();
+        if n < 2 {
+            return;
+        }
+
+        // every player is credited one virtual draw, spread over all opponents, so that the
+        // strengths of players without wins or losses are finite
+        let prior = 0.5 / (n - 1) as f64;
+        let mut wins = self.wins.clone();
+        for ((i, j), points) in wins.indexed_iter_mut() {
+            if i != j {
+                *points += prior;
+            }
+        }
+
+        let params = BradleyTerry::new(wins.clone())
+            .params()
+            .expect("the prior determines all parameters");
+        self.strengths = params.iter().map(|p| p.ln()).collect();
+
+        self.information = (0..n)
+            .map(|i| {
+                (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| {
+                        let p = win_probability(self.strengths[i], self.strengths[j]);
+                        (wins[[i, j]] + wins[[j, i]]) * p * (1.0 - p)
+                    })
+                    .sum()
+            })
+            .collect();
+    }
+
+    /// Pairs the next round by the expected information gain of every pairing. Every player
+    /// is paired at most once per round, and a round may be smaller than the batch size.
+    fn plan(&mut self) {
+        self.fit();
+        if self.is_confident() {
+            return;
+        }
+
+        let active = self.order(true);
+        let mut candidates = Vec::new();
+        for (k, &i) in active.iter().enumerate() {
+            for &j in &active[k + 1..] {
+                // pairs that are ordered with confidence already are not worth a match
+                let error = self.error_probability(i, j);
+                if error < 1.0 - self.confidence {
+                    continue;
+                }
+                let p = win_probability(self.strengths[i], self.strengths[j]);
+                let variance = 1.0 / self.information[i] + 1.0 / self.information[j];
+                candidates.push((error * p * (1.0 - p) * variance, i, j));
+            }
+        }
+        candidates.sort_by(|(g1, ..), (g2, ..)| g2.total_cmp(g1));
+
+        let batch = self.batch.unwrap_or(active.len() / 2);
+        let mut paired = vec![false; self.players.len()];
+        for (_, i, j) in candidates {
+            if self.pairs.len() >= batch {
+                break;
+            }
+            if paired[i] || paired[j] {
+                continue;
+            }
+            paired[i] = true;
+            paired[j] = true;
+
+            // the player that has been Player 1 less often plays as Player 1
+            let balance = |k: usize| 2 * self.as_player1[k] as isize - self.scheduled[k] as isize;
+            let (p1, p2) = if balance(i) <= balance(j) {
+                (i, j)
+            } else {
+                (j, i)
+            };
+            self.scheduled[p1] += 1;
+            self.scheduled[p2] += 1;
+            self.as_player1[p1] += 1;
+            self.pairs.push_back((self.players[p1], self.players[p2]));
+        }
+    }
+
+    fn award(&mut self, winner: PlayerId, loser: PlayerId, points: f64) {
+        if let (Some(i), Some(j)) = (self.index(winner), self.index(loser)) {
+            self.wins[[i, j]] += points;
+        }
+    }
+}
+
+impl Scheduler for ActiveScheduler {
+    fn init(&mut self) {
+        self.plan();
+    }
+
+    fn start_round(&mut self) {
+        if self.pairs.is_empty() {
+            self.plan();
+        }
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        match outcome {
+            LabelledOutcome::Win { winner, loser } => self.award(winner.id(), loser.id(), 1.0),
+            LabelledOutcome::Draw(p1, p2) => {
+                self.award(p1.id(), p2.id(), 0.5);
+                self.award(p2.id(), p1.id(), 0.5);
+            }
+        }
+    }
+
+    /// A forfeit counts as a loss of the player at fault.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.award(fault.opponent.id(), fault.player.id(), 1.0);
+    }
+
+    /// New players are paired from the next round on.
+    fn add_player(&mut self, player: PlayerId) {
+        if let Some(i) = self.index(player) {
+            self.active[i] = true;
+            return;
+        }
+
+        let n = self.players.len();
+        let mut wins = Array2::zeros((n + 1, n + 1));
+        wins.slice_mut(s![..n, ..n]).assign(&self.wins);
+        self.wins = wins;
+
+        self.players.push(player);
+        self.active.push(true);
+        self.scheduled.push(0);
+        self.as_player1.push(0);
+        self.strengths.push(0.0);
+        self.information.push(0.0);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        if let Some(i) = self.index(player) {
+            self.active[i] = false;
+            self.pairs.retain(|&(p1, p2)| p1 != player && p2 != player);
+        }
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.pairs.pop_front()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        // Active scheduler never blocks within a round
+        Ok(self.get())
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        // the number of rounds depends on the results
+        (self.pairs.len(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Outcome;
+    use crate::player::PlayerGen;
+    use crate::test_util::{repository, Unplayed};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_active() {
+        let players = repository::<Unplayed>(8);
+
+        // every player is 200 Elo stronger than the player with the next smaller id
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut scheduler = ActiveScheduler::new(players.ids());
+        scheduler.init();
+        let mut matches = 0;
+        while let Some((p1, p2)) = scheduler.get().or_else(|| {
+            scheduler.start_round();
+            scheduler.get()
+        }) {
+            let gap = (p2 as f64 - p1 as f64) * 200.0;
+            let outcome = if rng.gen_bool(1.0 / (1.0 + 10f64.powf(gap / 400.0))) {
+                Outcome::WinP1
+            } else {
+                Outcome::WinP2
+            };
+            let player1 = players.data(p1).unwrap();
+            let player2 = players.data(p2).unwrap();
+            scheduler.report(&LabelledOutcome::new(outcome, player1, player2));
+            matches += 1;
+        }
+
+        assert!(scheduler.is_confident());
+        assert_eq!(scheduler.ranking(), [7, 6, 5, 4, 3, 2, 1, 0]);
+        // repeated round robins need 20 to 30 cycles of 28 matches for the same confidence
+        assert!(matches < 5 * 28, "{} matches", matches);
+    }
+
+    #[test]
+    fn test_confidence_range() {
+        for confidence in [0.4, 1.0, 1.5, f64::NAN] {
+            assert!(matches!(
+                ActiveScheduler::new(vec![0, 1]).with_confidence(confidence),
+                Err(Error::Scheduler(_))
+            ));
+        }
+        for confidence in [0.5, 0.99] {
+            assert!(ActiveScheduler::new(vec![0, 1])
+                .with_confidence(confidence)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_players_join_and_retire() {
+        let mut players = repository::<Unplayed>(4);
+
+        let mut scheduler = ActiveScheduler::new(players.ids());
+        scheduler.init();
+        while let Some((p1, p2)) = scheduler.get() {
+            let player1 = players.data(p1).unwrap();
+            let player2 = players.data(p2).unwrap();
+            scheduler.report(&LabelledOutcome::Draw(player1, player2));
+        }
+
+        // the retired player is no longer paired, the new player is paired first, as it is
+        // the most uncertain
+        let new = players.add_player(PlayerGen::new(|| ()));
+        scheduler.remove_player(0);
+        scheduler.add_player(new);
+        scheduler.start_round();
+
+        let round = std::iter::from_fn(|| scheduler.get()).collect::<Vec<_>>();
+        assert_eq!(round.len(), 2);
+        assert!(round[0].0 == new || round[0].1 == new);
+        assert!(round.iter().all(|&(p1, p2)| p1 != 0 && p2 != 0));
+
+        // the retired player stays in the ranking
+        assert_eq!(scheduler.ranking().len(), 5);
+    }
+}
@@ -0,0 +1,537 @@
+/*------------------------------------------------------------------------------------------------*/
+/*---------------------------------- Scheduler Combinators ---------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::PlayerId;
+use crate::scheduling::{NextStage, Scheduler, Staged};
+use std::collections::{HashMap, VecDeque};
+
+/// Plays all rounds of a scheduler, then all rounds of another. See `SchedulerExt::followed_by`.
+pub type Chain<A, B> = Staged<A, B, NextStage<B>>;
+
+/// Combinators to compose schedulers into new formats, like `Iterator` adapters.
+pub trait SchedulerExt: Scheduler + Sized {
+    /// Plays the rounds of `other` once all rounds of this scheduler have been played.
+    /// Named to avoid a clash with `Iterator::chain`, which most schedulers implement as well.
+    fn followed_by<B: Scheduler + Send + 'static>(self, other: B) -> Chain<Self, B> {
+        Staged::new(self, Box::new(move |_: &[(PlayerId, f64)]| other))
+    }
+
+    /// Plays the rounds of a scheduler that is created from the standings at the end of this
+    /// scheduler's last round, e.g. a playoff of the best players.
+    fn then<B, F>(self, next: F) -> Staged<Self, B, F>
+    where
+        B: Scheduler,
+        F: FnOnce(&[(PlayerId, f64)]) -> B,
+    {
+        Staged::new(self, next)
+    }
+
+    /// Alternates between the pairings of this scheduler and `other`. A round ends once both
+    /// schedulers have ended their round.
+    fn interleave<B: Scheduler>(self, other: B) -> Interleave<Self, B> {
+        Interleave {
+            a: self,
+            b: other,
+            next_b: false,
+            origins: HashMap::new(),
+        }
+    }
+
+    /// Skips the pairings for which `predicate` returns false, e.g. to exclude known pairs.
+    fn filter_pairs<P>(self, predicate: P) -> FilterPairs<Self, P>
+    where
+        P: FnMut(&(PlayerId, PlayerId)) -> bool,
+    {
+        FilterPairs {
+            inner: self,
+            predicate,
+        }
+    }
+
+    /// Rewrites every pairing with `f`, e.g. to swap sides.
+    fn map_pairs<F>(self, f: F) -> MapPairs<Self, F>
+    where
+        F: FnMut((PlayerId, PlayerId)) -> (PlayerId, PlayerId),
+    {
+        MapPairs { inner: self, f }
+    }
+
+    /// Ends the tournament after `n` pairings.
+    fn limit(self, n: usize) -> Limit<Self> {
+        Limit {
+            inner: self,
+            remaining: n,
+        }
+    }
+}
+
+impl<S: Scheduler> SchedulerExt for S {}
+
+/*------------------------------------------------------------------------------------------------*/
+
+/// See `SchedulerExt::interleave`.
+pub struct Interleave<A, B> {
+    a: A,
+    b: B,
+    next_b: bool,
+    /// Which scheduler paired the players of the matches still in progress, by the ordered ids,
+    /// oldest first. Results are reported to the scheduler that paired the match. If both
+    /// schedulers pair the same players at the same time, the results are assumed to complete
+    /// in the order of the pairings.
+    origins: HashMap<(PlayerId, PlayerId), VecDeque<bool>>,
+}
+
+fn unordered(p1: PlayerId, p2: PlayerId) -> (PlayerId, PlayerId) {
+    (p1.min(p2), p1.max(p2))
+}
+
+impl<A: Scheduler, B: Scheduler> Interleave<A, B> {
+    /// Takes a pairing from the scheduler whose turn it is, or else from the other one.
+    fn pick<G>(&mut self, mut get: G) -> Result<Option<(PlayerId, PlayerId)>, Error>
+    where
+        G: FnMut(&mut dyn Scheduler) -> Result<Option<(PlayerId, PlayerId)>, Error>,
+    {
+        let mut blocked = None;
+        for from_b in [self.next_b, !self.next_b] {
+            let scheduler: &mut dyn Scheduler = if from_b { &mut self.b } else { &mut self.a };
+            match get(scheduler) {
+                Ok(Some((p1, p2))) => {
+                    self.next_b = !from_b;
+                    self.origins
+                        .entry(unordered(p1, p2))
+                        .or_default()
+                        .push_back(from_b);
+                    return Ok(Some((p1, p2)));
+                }
+                Ok(None) => {}
+                Err(error) => blocked = Some(error),
+            }
+        }
+        blocked.map_or(Ok(None), Err)
+    }
+
+    /// The scheduler that paired the players, if any. Every match is looked up once, when it
+    /// has completed.
+    fn origin(&mut self, p1: PlayerId, p2: PlayerId) -> Option<&mut dyn Scheduler> {
+        let pair = unordered(p1, p2);
+        let origins = self.origins.get_mut(&pair)?;
+        let from_b = origins.pop_front()?;
+        if origins.is_empty() {
+            self.origins.remove(&pair);
+        }
+        if from_b {
+            Some(&mut self.b)
+        } else {
+            Some(&mut self.a)
+        }
+    }
+}
+
+impl<A: Scheduler, B: Scheduler> Scheduler for Interleave<A, B> {
+    fn init(&mut self) {
+        self.a.init();
+        self.b.init();
+    }
+
+    fn start_round(&mut self) {
+        self.a.start_round();
+        self.b.start_round();
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.a.round_completed(standings);
+        self.b.round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        let (p1, p2) = outcome.players();
+        if let Some(scheduler) = self.origin(p1.id(), p2.id()) {
+            scheduler.report(outcome);
+        }
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        if let Some(scheduler) = self.origin(fault.player.id(), fault.opponent.id()) {
+            scheduler.report_fault(fault);
+        }
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        if let Some(scheduler) = self.origin(player1, player2) {
+            scheduler.report_failure(player1, player2);
+        }
+    }
+
+    /// New players join both schedulers.
+    fn add_player(&mut self, player: PlayerId) {
+        self.a.add_player(player);
+        self.b.add_player(player);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        self.a.remove_player(player);
+        self.b.remove_player(player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.pick(|scheduler| Ok(scheduler.get()))
+            .unwrap_or_default()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        self.pick(|scheduler| scheduler.try_get())
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        let (a_lower, a_upper) = self.a.remaining();
+        let (b_lower, b_upper) = self.b.remaining();
+        // either scheduler may be unbounded, e.g. a ladder
+        let upper = match (a_upper, b_upper) {
+            (Some(a), Some(b)) => a.checked_add(b),
+            _ => None,
+        };
+        (a_lower.saturating_add(b_lower), upper)
+    }
+}
+
+/*------------------------------------------------------------------------------------------------*/
+
+/// See `SchedulerExt::filter_pairs`.
+pub struct FilterPairs<S, P> {
+    inner: S,
+    predicate: P,
+}
+
+impl<S, P> Scheduler for FilterPairs<S, P>
+where
+    S: Scheduler,
+    P: FnMut(&(PlayerId, PlayerId)) -> bool,
+{
+    fn init(&mut self) {
+        self.inner.init();
+    }
+
+    fn start_round(&mut self) {
+        self.inner.start_round();
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.inner.round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        self.inner.report(outcome);
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.inner.report_fault(fault);
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        self.inner.report_failure(player1, player2);
+    }
+
+    fn add_player(&mut self, player: PlayerId) {
+        self.inner.add_player(player);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        self.inner.remove_player(player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        loop {
+            let pair = self.inner.get()?;
+            if (self.predicate)(&pair) {
+                return Some(pair);
+            }
+        }
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        loop {
+            let Some(pair) = self.inner.try_get()? else {
+                return Ok(None);
+            };
+            if (self.predicate)(&pair) {
+                return Ok(Some(pair));
+            }
+        }
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        // any number of the pairings may be skipped
+        (0, self.inner.remaining().1)
+    }
+}
+
+/*------------------------------------------------------------------------------------------------*/
+
+/// See `SchedulerExt::map_pairs`.
+pub struct MapPairs<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F> Scheduler for MapPairs<S, F>
+where
+    S: Scheduler,
+    F: FnMut((PlayerId, PlayerId)) -> (PlayerId, PlayerId),
+{
+    fn init(&mut self) {
+        self.inner.init();
+    }
+
+    fn start_round(&mut self) {
+        self.inner.start_round();
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.inner.round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        self.inner.report(outcome);
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.inner.report_fault(fault);
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        self.inner.report_failure(player1, player2);
+    }
+
+    fn add_player(&mut self, player: PlayerId) {
+        self.inner.add_player(player);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        self.inner.remove_player(player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.inner.get().map(&mut self.f)
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        Ok(self.inner.try_get()?.map(&mut self.f))
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        self.inner.remaining()
+    }
+}
+
+/*------------------------------------------------------------------------------------------------*/
+
+/// See `SchedulerExt::limit`.
+pub struct Limit<S> {
+    inner: S,
+    remaining: usize,
+}
+
+impl<S: Scheduler> Scheduler for Limit<S> {
+    fn init(&mut self) {
+        self.inner.init();
+    }
+
+    fn start_round(&mut self) {
+        self.inner.start_round();
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.inner.round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        self.inner.report(outcome);
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.inner.report_fault(fault);
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        self.inner.report_failure(player1, player2);
+    }
+
+    fn add_player(&mut self, player: PlayerId) {
+        self.inner.add_player(player);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        self.inner.remove_player(player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let pair = self.inner.get()?;
+        self.remaining -= 1;
+        Some(pair)
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        let pair = self.inner.try_get()?;
+        if pair.is_some() {
+            self.remaining -= 1;
+        }
+        Ok(pair)
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.inner.remaining();
+        let upper = upper.map_or(self.remaining, |upper| upper.min(self.remaining));
+        (lower.min(self.remaining), Some(upper))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduling::{GauntletScheduler, LadderScheduler, RoundRobbinScheduler};
+    use crate::test_util::{repository, Unplayed};
+
+    /// Pairs the given pairs, and records the winners and failures reported to it.
+    struct Recording {
+        pairs: VecDeque<(PlayerId, PlayerId)>,
+        winners: Vec<PlayerId>,
+        failures: usize,
+    }
+
+    impl Recording {
+        fn new(pairs: Vec<(PlayerId, PlayerId)>) -> Self {
+            Recording {
+                pairs: pairs.into(),
+                winners: Vec::new(),
+                failures: 0,
+            }
+        }
+    }
+
+    impl Scheduler for Recording {
+        fn report(&mut self, outcome: &LabelledOutcome) {
+            self.winners.push(outcome.players().0.id());
+        }
+
+        fn report_failure(&mut self, _: PlayerId, _: PlayerId) {
+            self.failures += 1;
+        }
+
+        fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+            self.pairs.pop_front()
+        }
+
+        fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+            Ok(self.get())
+        }
+    }
+
+    /// Plays the rounds of a scheduler like a tournament does, without reporting results.
+    fn rounds(mut scheduler: impl Scheduler) -> Vec<Vec<(PlayerId, PlayerId)>> {
+        scheduler.init();
+
+        let mut rounds = Vec::new();
+        loop {
+            let round = std::iter::from_fn(|| scheduler.get()).collect::<Vec<_>>();
+            if round.is_empty() {
+                return rounds;
+            }
+            rounds.push(round);
+            scheduler.round_completed(&[]);
+            scheduler.start_round();
+        }
+    }
+
+    #[test]
+    fn test_chain() {
+        let gauntlet = GauntletScheduler::new(0, vec![1, 2]);
+        let round_robin = RoundRobbinScheduler::new(vec![1, 2, 3, 4]);
+
+        let rounds = rounds(gauntlet.followed_by(round_robin));
+        assert_eq!(rounds.len(), 4);
+        assert_eq!(rounds[0], [(0, 1), (2, 0)]);
+        assert!(rounds[1..].iter().all(|round| round.len() == 2));
+    }
+
+    #[test]
+    fn test_interleave() {
+        let first = GauntletScheduler::new(0, vec![1, 2, 3]);
+        let second = GauntletScheduler::new(9, vec![7, 8]);
+
+        assert_eq!(
+            rounds(first.interleave(second)),
+            [[(0, 1), (9, 7), (2, 0), (8, 9), (0, 3)]]
+        );
+    }
+
+    #[test]
+    fn test_interleave_reports_to_origin() {
+        let players = repository::<Unplayed>(3);
+        let win = |winner, loser| LabelledOutcome::Win {
+            winner: players.data(winner).unwrap(),
+            loser: players.data(loser).unwrap(),
+        };
+
+        let first = Recording::new(vec![(0, 1), (1, 2)]);
+        let second = Recording::new(vec![(1, 0), (2, 0)]);
+        let mut scheduler = first.interleave(second);
+        let pairs = std::iter::from_fn(|| scheduler.get()).collect::<Vec<_>>();
+        assert_eq!(pairs, [(0, 1), (1, 0), (1, 2), (2, 0)]);
+
+        // the first match between 0 and 1 fails, so the next result is the second's
+        scheduler.report_failure(0, 1);
+        scheduler.report(&win(1, 0));
+        scheduler.report(&win(2, 0));
+        scheduler.report(&win(2, 1));
+
+        assert_eq!(scheduler.a.failures, 1);
+        assert_eq!(scheduler.a.winners, [2]);
+        assert_eq!(scheduler.b.failures, 0);
+        assert_eq!(scheduler.b.winners, [1, 2]);
+        assert!(scheduler.origins.is_empty());
+    }
+
+    #[test]
+    fn test_unbounded() {
+        let ladder = || LadderScheduler::new(vec![1, 2, 3]).with_seed(1);
+
+        let gauntlet = || GauntletScheduler::new(0, vec![1, 2]);
+        assert_eq!(
+            ladder().interleave(gauntlet()).remaining(),
+            (usize::MAX, None)
+        );
+        assert_eq!(
+            ladder().interleave(gauntlet()).limit(10).remaining(),
+            (10, Some(10))
+        );
+
+        let mut chained = ladder().followed_by(RoundRobbinScheduler::new(vec![1, 2, 3]));
+        chained.init();
+        chained.start_round();
+        assert_eq!(chained.remaining(), (usize::MAX, None));
+        assert!(chained.get().is_some());
+    }
+
+    #[test]
+    fn test_filter_map_limit() {
+        let scheduler = RoundRobbinScheduler::new(vec![1, 2, 3, 4, 5])
+            .filter_pairs(|&(p1, p2)| p1 != 1 && p2 != 1)
+            .map_pairs(|(p1, p2)| (p1.max(p2), p1.min(p2)));
+        let pairs = rounds(scheduler).concat();
+        assert_eq!(pairs.len(), 6);
+        assert!(pairs.iter().all(|&(p1, p2)| p1 > p2 && p2 != 1));
+
+        let scheduler = RoundRobbinScheduler::new(vec![1, 2, 3, 4]).limit(3);
+        assert_eq!(scheduler.remaining(), (3, Some(3)));
+        assert_eq!(rounds(scheduler).concat().len(), 3);
+    }
+}
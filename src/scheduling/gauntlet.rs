@@ -0,0 +1,85 @@
+/*------------------------------------------------------------------------------------------------*/
+/*------------------------------------ Gauntlet Scheduler ----------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::player::PlayerId;
+use crate::scheduling::{PlayerBalancing, Scheduler};
+use std::collections::VecDeque;
+
+/// Pairs a single challenger against every other player once.
+/// The challenger alternates between playing as Player 1 and Player 2.
+pub struct GauntletScheduler {
+    challenger: PlayerId,
+    pairs: VecDeque<(PlayerId, PlayerId)>,
+}
+
+impl GauntletScheduler {
+    pub fn new(challenger: PlayerId, opponents: Vec<PlayerId>) -> Self {
+        let pairs = opponents
+            .into_iter()
+            .filter(|&opponent| opponent != challenger)
+            .enumerate()
+            .map(|(i, opponent)| {
+                if i % 2 == 0 {
+                    (challenger, opponent)
+                } else {
+                    (opponent, challenger)
+                }
+            })
+            .collect();
+
+        GauntletScheduler { challenger, pairs }
+    }
+
+    pub fn challenger(&self) -> PlayerId {
+        self.challenger
+    }
+}
+
+impl Scheduler for GauntletScheduler {
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.pairs.pop_front()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        // Gauntlet scheduler never blocks
+        Ok(self.get())
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        (self.pairs.len(), Some(self.pairs.len()))
+    }
+}
+
+impl Iterator for GauntletScheduler {
+    type Item = (PlayerId, PlayerId);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.get()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining()
+    }
+}
+
+impl PlayerBalancing for GauntletScheduler {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gauntlet() {
+        let mut scheduler = GauntletScheduler::new(2, vec![0, 1, 2, 3, 4]);
+        assert_eq!(scheduler.challenger(), 2);
+        assert_eq!(scheduler.remaining(), (4, Some(4)));
+
+        // the challenger does not play itself, and alternates sides
+        let pairs = scheduler.by_ref().collect::<Vec<_>>();
+        assert_eq!(pairs, [(2, 0), (1, 2), (2, 3), (4, 2)]);
+        assert_eq!(scheduler.remaining(), (0, Some(0)));
+        assert_eq!(scheduler.try_get().unwrap(), None);
+    }
+}
@@ -0,0 +1,269 @@
+/*------------------------------------------------------------------------------------------------*/
+/*-------------------------------------- Group Scheduler -----------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::player::PlayerId;
+use crate::scheduling::knockout::bracket_order;
+use crate::scheduling::{KnockoutScheduler, NextStage, RoundRobbinScheduler, Scheduler, Staged};
+
+/// How seeded players are distributed among groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Draw {
+    /// Seeds are dealt to the groups in order, e.g. A B C A B C for three groups.
+    Seeded,
+    /// Seeds are dealt in alternating directions, e.g. A B C C B A for three groups, so that
+    /// the strength of the groups is more even.
+    Snake,
+}
+
+/// Distributes the players, seeded best first, among `count` groups.
+///
+/// Returns an error if there are no groups, or more groups than players.
+pub fn draw_groups(
+    players: Vec<PlayerId>,
+    count: usize,
+    draw: Draw,
+) -> Result<Vec<Vec<PlayerId>>, Error> {
+    if count == 0 || count > players.len() {
+        return Err(Error::Scheduler(format!(
+            "Cannot draw {} groups from {} players",
+            count,
+            players.len()
+        )));
+    }
+
+    let mut groups = vec![Vec::new(); count];
+    for (i, player) in players.into_iter().enumerate() {
+        let (pass, k) = (i / count, i % count);
+        let group = match draw {
+            Draw::Snake if pass % 2 == 1 => count - 1 - k,
+            _ => k,
+        };
+        groups[group].push(player);
+    }
+    Ok(groups)
+}
+
+/// Plays a round robin in each group. The rounds of all groups are played at the same time.
+pub struct GroupScheduler {
+    groups: Vec<Vec<PlayerId>>,
+    schedulers: Vec<RoundRobbinScheduler>,
+}
+
+impl GroupScheduler {
+    /// Returns an error if there are no groups, or a group is empty.
+    pub fn new(groups: Vec<Vec<PlayerId>>) -> Result<Self, Error> {
+        if groups.is_empty() || groups.iter().any(Vec::is_empty) {
+            return Err(Error::Scheduler("Groups must not be empty".into()));
+        }
+
+        let schedulers = groups
+            .iter()
+            .map(|group| RoundRobbinScheduler::new(group.clone()))
+            .collect();
+
+        Ok(GroupScheduler { groups, schedulers })
+    }
+
+    pub fn groups(&self) -> &[Vec<PlayerId>] {
+        &self.groups
+    }
+
+    /// Advances the top `k` players of each group to a knockout bracket, once all groups have
+    /// been played. See `qualifiers` for how they are seeded.
+    pub fn with_playoffs(
+        self,
+        k: usize,
+    ) -> Staged<Self, KnockoutScheduler, NextStage<KnockoutScheduler>> {
+        let groups = self.groups.clone();
+        Staged::new(
+            self,
+            Box::new(move |standings: &[(PlayerId, f64)]| {
+                KnockoutScheduler::new(qualifiers(&groups, standings, k))
+            }),
+        )
+    }
+}
+
+/// Returns the top `k` players of each group by the standings, seeded for a knockout bracket.
+/// Players without standings are placed last in their group.
+///
+/// Group winners are seeded first, in the order of their groups. The other qualifiers are seeded
+/// by their place in the group, and where possible into the other half of the bracket than the
+/// winner of their group. With two qualifiers per group, players of the same group can then only
+/// meet in the final.
+pub fn qualifiers(
+    groups: &[Vec<PlayerId>],
+    standings: &[(PlayerId, f64)],
+    k: usize,
+) -> Vec<PlayerId> {
+    let ranked = groups
+        .iter()
+        .map(|group| {
+            let mut ranked = standings
+                .iter()
+                .map(|&(player, _)| player)
+                .filter(|player| group.contains(player))
+                .collect::<Vec<_>>();
+            let unranked = group
+                .iter()
+                .filter(|player| !ranked.contains(player))
+                .copied()
+                .collect::<Vec<_>>();
+            ranked.extend(unranked);
+            ranked.truncate(k);
+            ranked
+        })
+        .collect::<Vec<_>>();
+
+    // whether each seed is placed in the upper half of the bracket
+    let count = ranked.iter().map(Vec::len).sum::<usize>();
+    let size = count.next_power_of_two();
+    let mut upper = vec![false; size];
+    for &seed in &bracket_order(size)[..size / 2] {
+        upper[seed] = true;
+    }
+
+    let mut seeds = Vec::with_capacity(count);
+    for place in 0..k {
+        let players = ranked
+            .iter()
+            .enumerate()
+            .filter_map(|(group, players)| Some((group, *players.get(place)?)))
+            .collect::<Vec<_>>();
+        let first = seeds.len();
+        let mut placed = vec![None; players.len()];
+
+        for (group, player) in players {
+            let mut free = (0..placed.len()).filter(|&slot| placed[slot].is_none());
+            // winners are seeded by group, and the other qualifiers into the other half than the
+            // winner of their group, if a seed is free there
+            let slot = free
+                .clone()
+                .find(|&slot| place > 0 && upper[first + slot] != upper[group])
+                .or_else(|| free.next())
+                .expect("a seed is free for every player");
+            placed[slot] = Some(player);
+        }
+        seeds.extend(placed.into_iter().flatten());
+    }
+    seeds
+}
+
+impl Scheduler for GroupScheduler {
+    fn init(&mut self) {
+        self.schedulers.iter_mut().for_each(Scheduler::init);
+    }
+
+    fn start_round(&mut self) {
+        self.schedulers.iter_mut().for_each(Scheduler::start_round);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.schedulers.iter_mut().find_map(Scheduler::get)
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        // Group scheduler never blocks
+        Ok(self.get())
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        let n = self
+            .schedulers
+            .iter()
+            .map(|scheduler| scheduler.remaining().0)
+            .fold(0, usize::saturating_add);
+        (n, Some(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::Standings;
+    use crate::test_util::{repository, Ids};
+    use crate::tournaments::{Tournament, TournamentEvent};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_draw_groups() {
+        let players = (0..7).collect::<Vec<_>>();
+        assert_eq!(
+            draw_groups(players.clone(), 3, Draw::Seeded).unwrap(),
+            [vec![0, 3, 6], vec![1, 4], vec![2, 5]]
+        );
+        assert_eq!(
+            draw_groups(players.clone(), 3, Draw::Snake).unwrap(),
+            [vec![0, 5, 6], vec![1, 4], vec![2, 3]]
+        );
+
+        assert!(matches!(
+            draw_groups(players.clone(), 0, Draw::Seeded),
+            Err(Error::Scheduler(_))
+        ));
+        assert!(matches!(
+            draw_groups(players, 8, Draw::Seeded),
+            Err(Error::Scheduler(_))
+        ));
+        assert!(matches!(
+            GroupScheduler::new(vec![vec![0, 1], vec![]]),
+            Err(Error::Scheduler(_))
+        ));
+    }
+
+    #[test]
+    fn test_qualifiers_of_four_groups() {
+        let groups = [vec![0, 4, 8], vec![1, 5, 9], vec![2, 6, 10], vec![3, 7, 11]];
+        let seeds = qualifiers(&groups, &[], 2);
+        assert_eq!(seeds, [0, 1, 2, 3, 5, 4, 7, 6]);
+
+        // the winner and the runner up of every group are in different halves of the bracket
+        let order = bracket_order(8);
+        let upper = &order[..4];
+        for group in &groups {
+            let half = |player| {
+                let seed = seeds.iter().position(|&p| p == player).unwrap();
+                upper.contains(&seed)
+            };
+            assert_ne!(half(group[0]), half(group[1]));
+        }
+    }
+
+    #[test]
+    fn test_group_stage_playoffs() {
+        let players = repository::<Ids>(8);
+
+        // seed the larger ids first, so groups are [7, 4, 3, 0] and [6, 5, 2, 1]
+        let seeds = players.ids().into_iter().rev().collect();
+        let scheduler = GroupScheduler::new(draw_groups(seeds, 2, Draw::Snake).unwrap())
+            .unwrap()
+            .with_playoffs(2);
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+
+        let rounds = Arc::new(AtomicUsize::new(0));
+        let counter = rounds.clone();
+        tournament.add_observer(move |event: &TournamentEvent| {
+            if let TournamentEvent::RoundCompleted { .. } = event {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        tournament.init();
+
+        let pairs = tournament
+            .by_ref()
+            .map(|output| {
+                let (outcome, _) = output.unwrap();
+                let (winner, loser) = outcome.players();
+                (winner.id(), loser.id())
+            })
+            .collect::<Vec<_>>();
+
+        // 2 groups of 6 matches in 3 rounds, then the semifinals and the final
+        assert_eq!(pairs.len(), 15);
+        assert_eq!(rounds.load(Ordering::SeqCst), 5);
+        assert_eq!(pairs[12..], [(7, 5), (6, 4), (7, 6)]);
+    }
+}
@@ -0,0 +1,288 @@
+/*------------------------------------------------------------------------------------------------*/
+/*------------------------------------ Knockout Scheduler ----------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::PlayerId;
+use crate::scheduling::Scheduler;
+use std::collections::{HashMap, VecDeque};
+
+/// A single elimination bracket. The winner of every pairing advances to the next round,
+/// until a single player, the champion, remains.
+///
+/// Players are seeded in the order they are given, best first, and placed in the bracket so
+/// that the top seeds meet as late as possible. If the number of players is not a power of two,
+/// the top seeds receive a bye in the first round.
+///
+/// A pairing is won by the player with more points from the matches reported for it, so a
+/// pairing of several games is decided by their total. Tied pairings, e.g. draws or matches
+/// that could not be played, are replayed with inverted sides in an extra round. If a pairing
+/// is still tied after the maximum number of replays, the higher seed advances.
+pub struct KnockoutScheduler {
+    /// The players remaining in the bracket, in bracket order. Adjacent entries form the
+    /// pairings of the current round, `None` is a bye.
+    bracket: Vec<Option<PlayerId>>,
+    /// The seed of every player, starting at 0 for the best.
+    seeds: HashMap<PlayerId, usize>,
+    /// The points of every paired player in the current round.
+    points: HashMap<PlayerId, f64>,
+    /// The number of times the tied pairings of the current round have been replayed.
+    replays: usize,
+    max_replays: usize,
+    pairs: VecDeque<(PlayerId, PlayerId)>,
+}
+
+/// Returns the seeds, starting at 0, in the order they are placed in a bracket of `size`.
+pub(crate) fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let n = order.len() * 2;
+        order = order
+            .into_iter()
+            .flat_map(|seed| [seed, n - 1 - seed])
+            .collect();
+    }
+    order
+}
+
+impl KnockoutScheduler {
+    pub fn new(players: Vec<PlayerId>) -> Self {
+        let bracket = bracket_order(players.len().next_power_of_two())
+            .into_iter()
+            .map(|seed| players.get(seed).copied())
+            .collect();
+        let seeds = players
+            .into_iter()
+            .enumerate()
+            .map(|(seed, player)| (player, seed))
+            .collect();
+
+        KnockoutScheduler {
+            bracket,
+            seeds,
+            points: HashMap::new(),
+            replays: 0,
+            max_replays: 3,
+            pairs: VecDeque::new(),
+        }
+    }
+
+    /// Sets how often a tied pairing is replayed before the higher seed advances.
+    /// The default is 3.
+    pub fn with_max_replays(mut self, max_replays: usize) -> Self {
+        self.max_replays = max_replays;
+        self
+    }
+
+    /// The winner of the bracket, once all rounds have been played.
+    pub fn champion(&self) -> Option<PlayerId> {
+        match self.bracket[..] {
+            [champion] => champion,
+            _ => None,
+        }
+    }
+
+    /// Pairs the players of the current round.
+    fn pair_round(&mut self) {
+        self.points.clear();
+        self.replays = 0;
+        for pairing in self.bracket.chunks(2) {
+            if let [Some(p1), Some(p2)] = *pairing {
+                self.points.insert(p1, 0.0);
+                self.points.insert(p2, 0.0);
+                self.pairs.push_back((p1, p2));
+            }
+        }
+    }
+
+    /// Returns the winner of a pairing of the current round, or `None` if it is tied and may
+    /// be replayed.
+    fn winner(&self, pairing: &[Option<PlayerId>]) -> Option<Option<PlayerId>> {
+        match *pairing {
+            [Some(p1), Some(p2)] => {
+                let (s1, s2) = (self.points[&p1], self.points[&p2]);
+                if s1 > s2 {
+                    Some(Some(p1))
+                } else if s2 > s1 {
+                    Some(Some(p2))
+                } else if self.replays >= self.max_replays {
+                    Some(Some(if self.seeds[&p1] < self.seeds[&p2] {
+                        p1
+                    } else {
+                        p2
+                    }))
+                } else {
+                    None
+                }
+            }
+            // byes advance
+            [p1, p2] => Some(p1.or(p2)),
+            _ => unreachable!("the bracket has an even number of entries"),
+        }
+    }
+}
+
+impl Scheduler for KnockoutScheduler {
+    fn init(&mut self) {
+        self.pair_round();
+    }
+
+    /// Replays the tied pairings of the current round, or advances the winners to the next.
+    fn start_round(&mut self) {
+        if self.bracket.len() < 2 {
+            return;
+        }
+
+        let winners = self
+            .bracket
+            .chunks(2)
+            .map(|pairing| self.winner(pairing))
+            .collect::<Option<Vec<_>>>();
+
+        match winners {
+            Some(winners) => {
+                self.bracket = winners;
+                if self.bracket.len() > 1 {
+                    self.pair_round();
+                }
+            }
+            None => {
+                let tied = self
+                    .bracket
+                    .chunks(2)
+                    .filter(|pairing| self.winner(pairing).is_none())
+                    .filter_map(|pairing| match *pairing {
+                        [Some(p1), Some(p2)] => Some((p1, p2)),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                self.replays += 1;
+                for (p1, p2) in tied {
+                    self.points.insert(p1, 0.0);
+                    self.points.insert(p2, 0.0);
+                    self.pairs.push_back((p2, p1));
+                }
+            }
+        }
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        let mut award = |player: PlayerId, points: f64| {
+            if let Some(total) = self.points.get_mut(&player) {
+                *total += points;
+            }
+        };
+
+        match outcome {
+            LabelledOutcome::Win { winner, .. } => award(winner.id(), 1.0),
+            LabelledOutcome::Draw(p1, p2) => {
+                award(p1.id(), 0.5);
+                award(p2.id(), 0.5);
+            }
+        }
+    }
+
+    /// The opponent of the player at fault wins the match.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        if let Some(total) = self.points.get_mut(&fault.opponent.id()) {
+            *total += 1.0;
+        }
+    }
+
+    /// The player is eliminated, and its opponents advance.
+    fn remove_player(&mut self, player: PlayerId) {
+        for entry in &mut self.bracket {
+            if *entry == Some(player) {
+                *entry = None;
+            }
+        }
+        self.pairs.retain(|&(p1, p2)| p1 != player && p2 != player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.pairs.pop_front()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        // Knockout scheduler never blocks within a round
+        Ok(self.get())
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        // the number of replays depends on the results
+        (self.pairs.len(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::Standings;
+    use crate::test_util::{by_strength, Strength};
+    use crate::tournaments::Tournament;
+
+    type KnockoutTournament = Tournament<Strength, KnockoutScheduler, Standings>;
+
+    fn tournament(strengths: &[usize]) -> KnockoutTournament {
+        let players = by_strength(strengths);
+
+        // seed the strongest players first
+        let mut seeds = players.ids();
+        seeds.sort_by_key(|&id| std::cmp::Reverse(strengths[id]));
+
+        let scheduler = KnockoutScheduler::new(seeds);
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+        tournament.set_seed(3);
+        tournament.init();
+        tournament
+    }
+
+    fn play(strengths: &[usize]) -> (Vec<(PlayerId, PlayerId)>, Standings) {
+        let mut tournament = tournament(strengths);
+        let pairs = tournament
+            .by_ref()
+            .map(|output| {
+                let (outcome, _) = output.unwrap();
+                let (winner, loser) = outcome.players();
+                (winner.id(), loser.id())
+            })
+            .collect();
+        (pairs, tournament.into_scoring())
+    }
+
+    #[test]
+    fn test_bracket_order() {
+        assert_eq!(bracket_order(8), [0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn test_byes() {
+        let (pairs, _) = play(&[0, 1, 2, 3, 4, 5]);
+        // the two top seeds have a bye in the first round
+        assert_eq!(pairs, [(2, 1), (3, 0), (5, 2), (4, 3), (5, 4)]);
+    }
+
+    #[test]
+    fn test_draws_are_replayed() {
+        let (_, standings) = play(&[1, 1]);
+        let records = standings.records();
+        assert_eq!(records[0].wins, 1);
+        assert_eq!(records[1].losses, 1);
+    }
+
+    #[test]
+    fn test_replays_are_bounded() {
+        let mut tournament = tournament(&[0, 0, 1]);
+        let outputs = tournament.by_ref().collect::<Vec<_>>();
+
+        // the first match and its three replays fail, then the higher seed advances
+        assert_eq!(outputs.len(), 5);
+        assert!(outputs[..4].iter().all(Result::is_err));
+        assert_eq!(tournament.scheduler().champion(), Some(2));
+
+        let (outcome, _) = outputs[4].as_ref().unwrap();
+        assert_eq!(outcome.players().1.id(), 0);
+    }
+}
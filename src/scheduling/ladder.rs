@@ -0,0 +1,308 @@
+/*------------------------------------------------------------------------------------------------*/
+/*------------------------------------- Ladder Scheduler -----------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::PlayerId;
+use crate::scheduling::Scheduler;
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::BTreeMap;
+
+const INITIAL_RATING: f64 = 1500.0;
+const INITIAL_DEVIATION: f64 = 350.0;
+const MIN_DEVIATION: f64 = 50.0;
+
+/// The rating of a player on the ladder.
+#[derive(Debug, Clone)]
+pub struct LadderRating {
+    /// An Elo rating, starting at 1500.
+    pub rating: f64,
+    /// The uncertainty of the rating. It shrinks with the number of games, and scales the
+    /// rating change of each game, so that the ratings of new players converge quickly.
+    pub deviation: f64,
+    /// The number of reported games.
+    pub games: usize,
+    /// The number of pairings, including matches in progress.
+    scheduled: usize,
+    /// The number of pairings as Player 1.
+    as_player1: usize,
+    /// Retired players keep their rating, but are no longer paired.
+    retired: bool,
+}
+
+impl Default for LadderRating {
+    fn default() -> Self {
+        LadderRating {
+            rating: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+            games: 0,
+            scheduled: 0,
+            as_player1: 0,
+            retired: false,
+        }
+    }
+}
+
+impl LadderRating {
+    /// The expected score against an opponent with the given rating.
+    fn expected(&self, opponent: f64) -> f64 {
+        1.0 / (1.0 + 10f64.powf((opponent - self.rating) / 400.0))
+    }
+
+    fn update(&mut self, opponent: f64, score: f64) {
+        // a K-factor of 70 for new players, and 10 for established ones
+        let k = self.deviation / 5.0;
+        self.rating += k * (score - self.expected(opponent));
+        self.games += 1;
+        self.deviation = (INITIAL_DEVIATION / (1.0 + self.games as f64).sqrt()).max(MIN_DEVIATION);
+    }
+}
+
+/// A continuous ladder, that never runs out of pairings.
+///
+/// Every pairing starts with the player that has been paired the least, preferring the
+/// player with the most uncertain rating. Its opponent is drawn at random, weighted towards
+/// players of similar rating and with few games. Ratings are updated from the reported results.
+///
+/// Players can join and retire at any time, see `Tournament::add_player` and
+/// `Tournament::retire_player`. Retired players keep their rating.
+pub struct LadderScheduler {
+    players: BTreeMap<PlayerId, LadderRating>,
+    rng: StdRng,
+}
+
+impl LadderScheduler {
+    pub fn new(players: Vec<PlayerId>) -> Self {
+        LadderScheduler {
+            players: players
+                .into_iter()
+                .map(|player| (player, LadderRating::default()))
+                .collect(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Seeds the random choice of opponents, to make the pairings reproducible.
+    pub fn with_seed(self, seed: u64) -> Self {
+        LadderScheduler {
+            rng: StdRng::seed_from_u64(seed),
+            ..self
+        }
+    }
+
+    pub fn rating(&self, player: PlayerId) -> Option<&LadderRating> {
+        self.players.get(&player)
+    }
+
+    /// Returns the rating of every player, including retired players, best first.
+    pub fn standings(&self) -> Vec<(PlayerId, f64)> {
+        let mut standings = self
+            .players
+            .iter()
+            .map(|(&player, rating)| (player, rating.rating))
+            .collect::<Vec<_>>();
+        standings.sort_by(|(_, r1), (_, r2)| r2.total_cmp(r1));
+        standings
+    }
+
+    fn active(&self) -> impl Iterator<Item = (&PlayerId, &LadderRating)> {
+        self.players.iter().filter(|(_, rating)| !rating.retired)
+    }
+
+    fn least_scheduled(&self) -> Option<usize> {
+        self.active().map(|(_, rating)| rating.scheduled).min()
+    }
+
+    /// Updates the ratings of both players, if they are on the ladder or have retired from it.
+    fn update(&mut self, player: PlayerId, opponent: PlayerId, score: f64) {
+        let (Some(r1), Some(r2)) = (self.players.get(&player), self.players.get(&opponent)) else {
+            return;
+        };
+        let (r1, r2) = (r1.rating, r2.rating);
+        self.players.get_mut(&player).unwrap().update(r2, score);
+        self.players
+            .get_mut(&opponent)
+            .unwrap()
+            .update(r1, 1.0 - score);
+    }
+}
+
+impl Scheduler for LadderScheduler {
+    /// Adds a player to the ladder, who is paired next. A retired player returns with its
+    /// previous rating.
+    fn add_player(&mut self, player: PlayerId) {
+        // new players are paired as much as the least active player on the ladder
+        let scheduled = self.least_scheduled().unwrap_or(0);
+        let rating = self.players.entry(player).or_default();
+        if rating.games == 0 || rating.retired {
+            rating.scheduled = scheduled;
+            rating.as_player1 = scheduled / 2;
+        }
+        rating.retired = false;
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        if let Some(rating) = self.players.get_mut(&player) {
+            rating.retired = true;
+        }
+    }
+
+    /// Returns `None` only if fewer than two players are on the ladder.
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        if self.active().count() < 2 {
+            return None;
+        }
+
+        // the least paired players, of which those with the most uncertain rating
+        let least = self.least_scheduled()?;
+        let candidates = self
+            .active()
+            .filter(|(_, rating)| rating.scheduled == least)
+            .collect::<Vec<_>>();
+        let deviation = candidates
+            .iter()
+            .map(|(_, rating)| rating.deviation)
+            .fold(f64::MIN, f64::max);
+        let candidates = candidates
+            .into_iter()
+            .filter(|(_, rating)| rating.deviation == deviation)
+            .map(|(&player, _)| player)
+            .collect::<Vec<_>>();
+        let player = *candidates.choose(&mut self.rng)?;
+
+        // opponents are weighted by the variance of the result, which is the largest for
+        // players of equal strength, and by how much less they have been paired
+        let rating = &self.players[&player];
+        let (opponents, weights): (Vec<_>, Vec<_>) = self
+            .active()
+            .filter(|(&opponent, _)| opponent != player)
+            .map(|(&opponent, other)| {
+                let p = rating.expected(other.rating);
+                let weight = p * (1.0 - p) / (1 + other.scheduled - least) as f64;
+                (opponent, weight)
+            })
+            .unzip();
+        let opponent = opponents[WeightedIndex::new(weights).ok()?.sample(&mut self.rng)];
+
+        // the player that has been Player 1 less often plays as Player 1
+        let balance =
+            |rating: &LadderRating| 2 * rating.as_player1 as isize - rating.scheduled as isize;
+        let (p1, p2) = if balance(&self.players[&player]) <= balance(&self.players[&opponent]) {
+            (player, opponent)
+        } else {
+            (opponent, player)
+        };
+
+        let r1 = self.players.get_mut(&p1).unwrap();
+        r1.scheduled += 1;
+        r1.as_player1 += 1;
+        self.players.get_mut(&p2).unwrap().scheduled += 1;
+        Some((p1, p2))
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        // Ladder scheduler never blocks
+        Ok(self.get())
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        match outcome {
+            LabelledOutcome::Win { winner, loser } => self.update(winner.id(), loser.id(), 1.0),
+            LabelledOutcome::Draw(p1, p2) => self.update(p1.id(), p2.id(), 0.5),
+        }
+    }
+
+    /// A forfeit is rated as a loss of the player at fault.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.update(fault.opponent.id(), fault.player.id(), 1.0);
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        if self.active().count() < 2 {
+            (0, Some(0))
+        } else {
+            (usize::MAX, None)
+        }
+    }
+}
+
+impl Iterator for LadderScheduler {
+    type Item = (PlayerId, PlayerId);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.get()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Outcome;
+    use crate::player::{PlayerGen, PlayerRepository};
+    use crate::test_util::{repository, Unplayed};
+
+    /// Plays `games` matches of the ladder, which the player with the larger id wins.
+    fn play(ladder: &mut LadderScheduler, players: &PlayerRepository<Unplayed>, games: usize) {
+        for (p1, p2) in ladder.by_ref().take(games).collect::<Vec<_>>() {
+            let outcome = if p1 > p2 {
+                Outcome::WinP1
+            } else {
+                Outcome::WinP2
+            };
+            let player1 = players.get_instance(p1).unwrap().data();
+            let player2 = players.get_instance(p2).unwrap().data();
+            ladder.report(&LabelledOutcome::new(outcome, player1, player2));
+        }
+    }
+
+    #[test]
+    fn test_ladder() {
+        let mut players = repository::<Unplayed>(6);
+
+        let mut ladder = LadderScheduler::new(players.ids()).with_seed(5);
+        play(&mut ladder, &players, 300);
+
+        let order = ladder
+            .standings()
+            .into_iter()
+            .map(|(player, _)| player)
+            .collect::<Vec<_>>();
+        assert_eq!(order, [5, 4, 3, 2, 1, 0]);
+
+        // everyone plays about the same number of games
+        let games = players
+            .ids()
+            .into_iter()
+            .map(|player| ladder.rating(player).unwrap().games)
+            .collect::<Vec<_>>();
+        assert!(games.iter().all(|&games| games >= 90), "{:?}", games);
+
+        // a new player is paired next, and climbs the ladder quickly
+        let new = players.add_player(PlayerGen::new(|| ()));
+        ladder.add_player(new);
+        let (p1, p2) = ladder.get().unwrap();
+        assert!(p1 == new || p2 == new);
+
+        play(&mut ladder, &players, 100);
+        let games = ladder.rating(new).unwrap().games;
+        let place = ladder
+            .standings()
+            .iter()
+            .position(|&(player, _)| player == new);
+        assert!(
+            games < 40 && place < Some(2),
+            "{} games, place {:?}",
+            games,
+            place
+        );
+    }
+}
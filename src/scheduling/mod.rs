@@ -1,9 +1,26 @@
+pub mod active;
+pub mod combinators;
+pub mod gauntlet;
+pub mod groups;
+pub mod knockout;
+pub mod ladder;
 pub mod round_robbin;
+pub mod staged;
+pub mod swiss;
 
+pub use active::*;
+pub use combinators::*;
+pub use gauntlet::*;
+pub use groups::*;
+pub use knockout::*;
+pub use ladder::*;
 pub use round_robbin::*;
+pub use staged::*;
+pub use swiss::*;
 
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
 use crate::player::PlayerId;
-use anyhow::Error;
 
 /// A scheduler is responsible for pairing players for a match.
 pub trait Scheduler {
@@ -11,9 +28,50 @@ pub trait Scheduler {
     /// Used to perform any necessary initialization.
     fn init(&mut self) {}
 
-    /// Hint to the pairing policy that a new round is starting.
+    /// Starts the next round. Called by the tournament once the scheduler has no more pairings
+    /// for the current round, and all of its matches have completed. If the scheduler still has
+    /// no pairing after a new round has been started, the tournament is over.
+    ///
+    /// The first round is started by `init`.
     fn start_round(&mut self) {}
 
+    /// Called by the tournament when a round has completed, with the standings of the scoring
+    /// system, best first. Schedulers that pair by standings, e.g. Swiss systems, can use them
+    /// to pair the next round.
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        let _ = standings;
+    }
+
+    /// Called by the tournament with the outcome of every completed match. Schedulers whose
+    /// pairings depend on results, e.g. knockout brackets, use it to decide who advances.
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        let _ = outcome;
+    }
+
+    /// Called by the tournament when a match was forfeited by the player at fault.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        let _ = fault;
+    }
+
+    /// Called by the tournament when a match between the players could not be played, and
+    /// neither of them was at fault. The match is not scored.
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        let _ = (player1, player2);
+    }
+
+    /// Called by the tournament when a player joins. Schedulers with a fixed schedule ignore
+    /// new players.
+    fn add_player(&mut self, player: PlayerId) {
+        let _ = player;
+    }
+
+    /// Called by the tournament when a player retires. Schedulers with a fixed schedule may
+    /// keep the pairings of the player, which the tournament then drops or forfeits. Others
+    /// should no longer pair the player.
+    fn remove_player(&mut self, player: PlayerId) {
+        let _ = player;
+    }
+
     /// Returns the next pairing of player to play a match between, or None to notify executors
     /// that the tournament is over, the round has ended, or no more pairings are available.
     ///
@@ -23,9 +81,61 @@ pub trait Scheduler {
     ///
     fn get(&mut self) -> Option<(PlayerId, PlayerId)>;
 
-    /// A nonblocking counterpart of `get()` that returns an `Error`, if
+    /// A nonblocking counterpart of `get()` that returns `Error::WouldBlock`, if
     /// the function were to block.
     fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error>;
+
+    /// Returns the bounds on the number of remaining pairings, like `Iterator::size_hint`.
+    /// An upper bound of `None` means that the number is unknown or unbounded.
+    fn remaining(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<T: Scheduler + ?Sized> Scheduler for Box<T> {
+    fn init(&mut self) {
+        (**self).init()
+    }
+
+    fn start_round(&mut self) {
+        (**self).start_round()
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        (**self).round_completed(standings)
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        (**self).report(outcome)
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        (**self).report_fault(fault)
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        (**self).report_failure(player1, player2)
+    }
+
+    fn add_player(&mut self, player: PlayerId) {
+        (**self).add_player(player)
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        (**self).remove_player(player)
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        (**self).get()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        (**self).try_get()
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        (**self).remaining()
+    }
 }
 
 /*------------------------------------------------------------------------------------------------*/
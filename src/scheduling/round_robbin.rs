@@ -2,15 +2,28 @@
 /*--------------------------------- Round Robbin Scheduler ---------------------------------------*/
 /*------------------------------------------------------------------------------------------------*/
 
+use crate::error::Error;
 use crate::player::PlayerId;
 use crate::scheduling::{PlayerBalancing, Scheduler};
-use anyhow::{anyhow, Error};
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+
+/// The pairings of a single round, and the player that sits out, if the number of players
+/// is odd.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Round {
+    pub pairings: Vec<(PlayerId, PlayerId)>,
+    pub bye: Option<PlayerId>,
+}
 
 pub struct RoundRobbinScheduler {
     players: Vec<Option<PlayerId>>,
-    pairs: Option<Vec<(PlayerId, PlayerId)>>,
-    r: usize,
+    /// The pairs of the current and all following rounds, once initialised.
+    rounds: Option<VecDeque<VecDeque<(PlayerId, PlayerId)>>>,
+    /// The rotation value of each cycle.
+    rotations: Vec<usize>,
+    /// Use the FIDE Berger tables instead of the circle method.
+    berger: bool,
 }
 
 fn gcd(a: usize, b: usize) -> usize {
@@ -28,7 +41,7 @@ fn gcd(a: usize, b: usize) -> usize {
 
 impl RoundRobbinScheduler {
     pub fn new(players: Vec<PlayerId>) -> Self {
-        let mut players = players.into_iter().map(|p| Some(p)).collect::<Vec<_>>();
+        let mut players = players.into_iter().map(Some).collect::<Vec<_>>();
 
         // If the number of players is odd, add a bye player.
         // For scheduling convenience, the bye player will be fixed in place.
@@ -38,106 +51,272 @@ impl RoundRobbinScheduler {
 
         RoundRobbinScheduler {
             players,
-            r: 1,
-            pairs: None,
+            rounds: None,
+            rotations: vec![1],
+            berger: false,
         }
     }
 
     /// Set the rotation value to get a different round robin tournament
     /// The value must be coprime to `(#players-1)` including the bye player.
-    /// The default value is 1.
+    /// The default value is 1. Applies to all cycles.
     ///
     /// Returns an error if the value is not coprime to `(#players-1)` or
     /// the value is out of bounds.
     pub fn with_r(self, r: usize) -> Result<Self, Error> {
+        self.check_r(r)?;
+        let rotations = vec![r; self.rotations.len()];
+        Ok(RoundRobbinScheduler { rotations, ..self })
+    }
+
+    /// Play `k` full cycles, e.g. 2 for a double round robin. The sides of every pairing are
+    /// inverted in every other cycle. All cycles use the rotation value of `with_r`.
+    ///
+    /// Returns an error if `k` is 0.
+    pub fn with_cycles(self, k: usize) -> Result<Self, Error> {
+        if k == 0 {
+            return Err(Error::Scheduler("at least one cycle is required".into()));
+        }
+        let rotations = vec![self.rotations[0]; k];
+        Ok(RoundRobbinScheduler { rotations, ..self })
+    }
+
+    /// Play one cycle per rotation value, so that the order of the rounds differs between
+    /// cycles. The sides of every pairing are inverted in every other cycle.
+    ///
+    /// Returns an error if no rotation value is given, or if any of them is invalid for
+    /// `with_r`.
+    pub fn with_cycle_rotations(self, rotations: Vec<usize>) -> Result<Self, Error> {
+        if rotations.is_empty() {
+            return Err(Error::Scheduler("at least one cycle is required".into()));
+        }
+        rotations.iter().try_for_each(|&r| self.check_r(r))?;
+        Ok(RoundRobbinScheduler { rotations, ..self })
+    }
+
+    /// Pair the players by the Berger tables published by FIDE, instead of the circle method.
+    /// The players are numbered in the order they were given, starting at 1, and Player 1 of
+    /// every pairing is the player with the white pieces. If the number of players is odd, the
+    /// opponent of the last number has the bye.
+    ///
+    /// The rotation values of `with_r` are ignored, the number of cycles is not.
+    pub fn with_berger_tables(self) -> Self {
+        RoundRobbinScheduler {
+            berger: true,
+            ..self
+        }
+    }
+
+    /// The number of cycles.
+    pub fn cycles(&self) -> usize {
+        self.rotations.len()
+    }
+
+    fn check_r(&self, r: usize) -> Result<(), Error> {
         if r == 0 || r >= self.players.len() {
-            return Err(anyhow!("r must be in range [1, n-1]"));
+            return Err(Error::Scheduler("r must be in range [1, n-1]".into()));
         }
 
         if gcd(r, self.players.len() - 1) == 1 {
-            Ok(RoundRobbinScheduler { r, ..self })
+            Ok(())
         } else {
-            Err(anyhow!(
-                "r must be coprime to (n-1), including the bye player"
+            Err(Error::Scheduler(
+                "r must be coprime to (n-1), including the bye player".into(),
             ))
         }
     }
-}
 
-impl Scheduler for RoundRobbinScheduler {
-    /// Generates all distinct pairs for the round robin tournament.
-    /// The number of rounds is n-1, where n is the number of players.
-    fn init(&mut self) {
-        if self.pairs.is_some() {
-            eprintln!("Warning: RoundRobbinScheduler init method has already been called once");
+    /// Returns the full schedule, one entry per round in the order the rounds are played.
+    /// The number of rounds is n-1 per cycle, where n is the number of players including the
+    /// bye player.
+    ///
+    /// The schedule does not depend on the progress of the tournament, so it can be published
+    /// before the first match is played. It is empty if there are fewer than two players.
+    pub fn schedule(&self) -> Vec<Round> {
+        if self.players.iter().flatten().count() < 2 {
+            return Vec::new();
+        }
+
+        let mut schedule = self.cycle(self.rotations[0]);
+
+        // The sides of a pairing depend on the rotation value, so the later cycles take them
+        // from the first cycle, inverted in every other cycle.
+        let sides = schedule
+            .iter()
+            .flat_map(|round| round.pairings.iter().copied())
+            .collect::<HashSet<_>>();
+
+        for (cycle, &r) in self.rotations.iter().enumerate().skip(1) {
+            let mut rounds = self.cycle(r);
+            for (p1, p2) in rounds.iter_mut().flat_map(|round| &mut round.pairings) {
+                if sides.contains(&(*p1, *p2)) == (cycle % 2 == 1) {
+                    std::mem::swap(p1, p2);
+                }
+            }
+            schedule.extend(rounds);
+        }
+        schedule
+    }
+
+    /// Returns the rounds of a single cycle with rotation value `r`.
+    fn cycle(&self, r: usize) -> Vec<Round> {
+        if self.berger {
+            return self.berger_cycle();
         }
 
-        let mut pairs = Vec::new();
+        let mut schedule = Vec::new();
 
         // Isolate the first player, and fix it in place.
         let fixed_player = self.players[0];
         let mut rest = self.players[1..].iter().cloned().collect::<VecDeque<_>>();
 
         let offset = self.players.len() / 2;
-        let rounds = self.players.len() - 1;
 
-        for round in 0..rounds {
+        for round in 0..self.players.len() - 1 {
+            let mut pairings = Vec::new();
+            let mut bye = None;
             let p1 = fixed_player;
             let p2 = rest[0].unwrap();
 
-            if p1.is_some() {
-                // else p1 is bye player and we skip this pair
+            match p1 {
                 // Alternate for fairness to each side
-                if round % 2 == 0 {
-                    pairs.push((p1.unwrap(), p2));
-                } else {
-                    pairs.push((p2, p1.unwrap()));
-                }
+                Some(p1) if round % 2 == 0 => pairings.push((p1, p2)),
+                Some(p1) => pairings.push((p2, p1)),
+                // p1 is the bye player, so p2 sits out this round
+                None => bye = Some(p2),
             }
 
             for i in 1..offset {
                 let p1 = rest[i].unwrap();
                 let p2 = rest[rest.len() - i].unwrap();
 
-                pairs.push((p1, p2));
+                pairings.push((p1, p2));
             }
+            schedule.push(Round { pairings, bye });
+
             // rotate the players, while fixing the first player
-            rest.rotate_right(self.r);
+            rest.rotate_right(r);
+        }
+
+        schedule
+    }
+
+    /// Returns the rounds of the Berger table for n players, where n includes the bye player.
+    ///
+    /// Round i starts at number f = 1 + (i * n/2) mod (n-1). On board 1, f plays n, with
+    /// white alternating between them. The other boards pair the numbers that follow f,
+    /// cyclic in 1..n-1, from the outside in.
+    fn berger_cycle(&self) -> Vec<Round> {
+        // number the players from 1 to n, with the bye player last
+        let mut players = self
+            .players
+            .iter()
+            .flatten()
+            .copied()
+            .map(Some)
+            .collect::<Vec<_>>();
+        if players.len() % 2 == 1 {
+            players.push(None);
+        }
+        let n = players.len();
+
+        (0..n - 1)
+            .map(|i| {
+                let f = 1 + (i * n / 2) % (n - 1);
+                let number = |k: usize| (f - 1 + k) % (n - 1) + 1;
+
+                let mut boards = vec![if i % 2 == 0 { (f, n) } else { (n, f) }];
+                boards.extend((1..n / 2).map(|k| (number(k), number(n - 1 - k))));
+
+                let mut round = Round {
+                    pairings: Vec::new(),
+                    bye: None,
+                };
+                for (white, black) in boards {
+                    match (players[white - 1], players[black - 1]) {
+                        (Some(white), Some(black)) => round.pairings.push((white, black)),
+                        (Some(player), None) | (None, Some(player)) => round.bye = Some(player),
+                        (None, None) => unreachable!("there is at most one bye player"),
+                    }
+                }
+                round
+            })
+            .collect()
+    }
+}
+
+impl Scheduler for RoundRobbinScheduler {
+    /// Generates all pairs for the round robin tournament, and starts the first round.
+    /// The number of rounds is n-1 per cycle, where n is the number of players.
+    fn init(&mut self) {
+        if self.rounds.is_some() {
+            eprintln!("Warning: RoundRobbinScheduler init method has already been called once");
+        }
+
+        let rounds = self
+            .schedule()
+            .into_iter()
+            .map(|round| round.pairings.into())
+            .collect();
+        self.rounds = Some(rounds);
+    }
+
+    /// Moves on to the next round, once all pairs of the current round have been handed out.
+    fn start_round(&mut self) {
+        if let Some(rounds) = &mut self.rounds {
+            if rounds.front().is_some_and(VecDeque::is_empty) {
+                rounds.pop_front();
+            }
         }
-        self.pairs = Some(pairs);
     }
 
     fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
-        if self.pairs.is_none() {
+        if self.rounds.is_none() {
             // TODO remove this
             eprintln!("Warning: RoundRobbinScheduler get method called before init method");
             self.init();
         }
 
-        if self.pairs.as_ref().unwrap().is_empty() {
-            None
-        } else {
-            Some(self.pairs.as_mut().unwrap().pop().unwrap())
-        }
+        // returns None at the end of every round
+        self.rounds.as_mut().unwrap().front_mut()?.pop_front()
     }
 
     fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
         // Round robbin scheduler never blocks
         Ok(self.get())
     }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        let n = match &self.rounds {
+            Some(rounds) => rounds.iter().map(VecDeque::len).sum(),
+            None => {
+                // exclude the bye player
+                let n = self.players.iter().flatten().count();
+                n * n.saturating_sub(1) / 2 * self.cycles()
+            }
+        };
+        (n, Some(n))
+    }
 }
 
 impl Iterator for RoundRobbinScheduler {
     type Item = (PlayerId, PlayerId);
 
+    /// Returns the pairs of all rounds, starting new rounds as needed.
     fn next(&mut self) -> Option<Self::Item> {
-        self.get()
+        loop {
+            if let Some(pair) = self.get() {
+                return Some(pair);
+            }
+            if !matches!(&self.rounds, Some(rounds) if rounds.len() > 1) {
+                return None;
+            }
+            self.start_round();
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let n = self.players.len();
-        let n = n * (n - 1) / 2;
-        (n, Some(n))
+        self.remaining()
     }
 }
 
@@ -186,7 +365,7 @@ mod tests {
             *count.entry(p1).or_insert(0) += 1;
             *count.entry(p2).or_insert(0) -= 1;
         }
-        let count: Vec<i32> = count.into_iter().map(|(_, c)| c).collect();
+        let count: Vec<i32> = count.into_values().collect();
         println!("{:?}", count);
         for c in &count {
             assert!(
@@ -196,4 +375,149 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_rounds() {
+        let players = vec![1, 2, 3, 4, 5, 6, 7];
+        let mut round_robin = RoundRobbinScheduler::new(players);
+        round_robin.init();
+
+        let mut rounds = 0;
+        while let Some(pair) = round_robin.get() {
+            // every player plays at most once per round
+            let mut round = vec![pair.0, pair.1];
+            while let Some((p1, p2)) = round_robin.get() {
+                round.extend([p1, p2]);
+            }
+            assert_eq!(round.len(), 6);
+            round.sort();
+            round.dedup();
+            assert_eq!(round.len(), 6, "A player plays twice in round {}", rounds);
+
+            rounds += 1;
+            round_robin.start_round();
+        }
+        assert_eq!(rounds, 7);
+        assert_eq!(round_robin.remaining(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_schedule() {
+        let players = vec![1, 2, 3, 4, 5];
+        let mut round_robin = RoundRobbinScheduler::new(players.clone());
+        let schedule = round_robin.schedule();
+        assert_eq!(schedule.len(), 5);
+
+        // every player sits out exactly once
+        let mut byes = schedule
+            .iter()
+            .filter_map(|round| round.bye)
+            .collect::<Vec<_>>();
+        byes.sort();
+        assert_eq!(byes, players);
+
+        // the scheduler hands out the pairings in the order of the schedule
+        round_robin.init();
+        let pairs = schedule
+            .into_iter()
+            .flat_map(|round| round.pairings)
+            .collect::<Vec<_>>();
+        assert_eq!(round_robin.collect::<Vec<_>>(), pairs);
+    }
+
+    #[test]
+    fn test_too_few_players() {
+        for players in [vec![], vec![1]] {
+            for mut round_robin in [
+                RoundRobbinScheduler::new(players.clone()),
+                RoundRobbinScheduler::new(players.clone()).with_berger_tables(),
+            ] {
+                assert!(round_robin.schedule().is_empty());
+                assert_eq!(round_robin.remaining(), (0, Some(0)));
+                round_robin.init();
+                assert_eq!(round_robin.next(), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycles() {
+        let players = vec![1, 2, 3, 4, 5, 6];
+        let n = players.len();
+        let round_robin = RoundRobbinScheduler::new(players)
+            .with_cycle_rotations(vec![1, 2, 3])
+            .unwrap();
+        assert_eq!(round_robin.remaining(), (3 * n * (n - 1) / 2, Some(45)));
+
+        let schedule = round_robin.schedule();
+        assert_eq!(schedule.len(), 3 * (n - 1));
+
+        let cycles = schedule
+            .chunks(n - 1)
+            .map(|cycle| {
+                cycle
+                    .iter()
+                    .flat_map(|round| round.pairings.iter().copied())
+                    .collect::<HashSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // the second cycle plays every pairing with inverted sides
+        let inverted = cycles[0].iter().map(|&(p1, p2)| (p2, p1)).collect();
+        assert_eq!(cycles[1], inverted);
+        assert_eq!(cycles[2], cycles[0]);
+
+        // but in a different order
+        assert_ne!(schedule[1].pairings, schedule[n].pairings);
+
+        assert!(RoundRobbinScheduler::new(vec![1, 2, 3, 4])
+            .with_cycle_rotations(vec![1, 3])
+            .is_err());
+        assert!(RoundRobbinScheduler::new(vec![1, 2])
+            .with_cycles(0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_berger_tables() {
+        // FIDE Handbook C.05, Annex 1
+        let tables: [&[&[(usize, usize)]]; 3] = [
+            &[&[(1, 4), (2, 3)], &[(4, 3), (1, 2)], &[(2, 4), (3, 1)]],
+            &[
+                &[(1, 6), (2, 5), (3, 4)],
+                &[(6, 4), (5, 3), (1, 2)],
+                &[(2, 6), (3, 1), (4, 5)],
+                &[(6, 5), (1, 4), (2, 3)],
+                &[(3, 6), (4, 2), (5, 1)],
+            ],
+            &[
+                &[(1, 8), (2, 7), (3, 6), (4, 5)],
+                &[(8, 5), (6, 4), (7, 3), (1, 2)],
+                &[(2, 8), (3, 1), (4, 7), (5, 6)],
+                &[(8, 6), (7, 5), (1, 4), (2, 3)],
+                &[(3, 8), (4, 2), (5, 1), (6, 7)],
+                &[(8, 7), (1, 6), (2, 5), (3, 4)],
+                &[(4, 8), (5, 3), (6, 2), (7, 1)],
+            ],
+        ];
+
+        for table in tables {
+            let n = table.len() + 1;
+            let schedule = RoundRobbinScheduler::new((1..=n).collect())
+                .with_berger_tables()
+                .schedule();
+            for (round, expected) in schedule.iter().zip(table) {
+                assert_eq!(round.pairings, *expected);
+                assert_eq!(round.bye, None);
+            }
+        }
+
+        // with an odd number of players, the opponent of the last number has the bye
+        let schedule = RoundRobbinScheduler::new(vec![1, 2, 3])
+            .with_berger_tables()
+            .schedule();
+        let byes = schedule.iter().map(|round| round.bye).collect::<Vec<_>>();
+        assert_eq!(byes, [Some(1), Some(3), Some(2)]);
+        assert_eq!(schedule[1].pairings, [(1, 2)]);
+    }
 }
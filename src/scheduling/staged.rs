@@ -0,0 +1,241 @@
+/*------------------------------------------------------------------------------------------------*/
+/*------------------------------------- Staged Scheduler -----------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::PlayerId;
+use crate::scheduling::Scheduler;
+
+/// Creates the second stage of a `Staged` scheduler from the standings of the first.
+pub type NextStage<B> = Box<dyn FnOnce(&[(PlayerId, f64)]) -> B + Send>;
+
+/// Plays the rounds of a first scheduler, and then those of a second scheduler, which is
+/// created from the standings at the end of the first stage, e.g. to seed a playoff.
+pub struct Staged<A, B, F> {
+    first: A,
+    next: Option<F>,
+    second: Option<B>,
+    /// The standings at the end of the last completed round.
+    standings: Vec<(PlayerId, f64)>,
+    /// Whether a round of the first stage has started, but none of its pairings was taken yet.
+    /// If the round has no pairings, the first stage is over.
+    starting: bool,
+    /// Players that retired during the first stage, which are removed from the second as well.
+    removed: Vec<PlayerId>,
+}
+
+impl<A, B, F> Staged<A, B, F>
+where
+    A: Scheduler,
+    B: Scheduler,
+    F: FnOnce(&[(PlayerId, f64)]) -> B,
+{
+    /// `next` creates the second stage from the standings, best first.
+    pub fn new(first: A, next: F) -> Self {
+        Staged {
+            first,
+            next: Some(next),
+            second: None,
+            standings: Vec::new(),
+            starting: false,
+            removed: Vec::new(),
+        }
+    }
+
+    /// The scheduler of the current stage.
+    fn current(&mut self) -> &mut dyn Scheduler {
+        match &mut self.second {
+            Some(second) => second,
+            None => &mut self.first,
+        }
+    }
+
+    /// Creates the second stage from the standings at the end of the first.
+    fn start_second(&mut self) -> &mut B {
+        let next = self.next.take().expect("second stage is created once");
+        let mut second = next(&self.standings);
+        second.init();
+        for &player in &self.removed {
+            second.remove_player(player);
+        }
+        self.second.insert(second)
+    }
+}
+
+impl<A, B, F> Scheduler for Staged<A, B, F>
+where
+    A: Scheduler,
+    B: Scheduler,
+    F: FnOnce(&[(PlayerId, f64)]) -> B,
+{
+    fn init(&mut self) {
+        self.first.init();
+        self.starting = true;
+    }
+
+    /// Starts the next round of the current stage. The second stage is started once a round of
+    /// the first stage has no pairings.
+    fn start_round(&mut self) {
+        match &mut self.second {
+            Some(second) => second.start_round(),
+            None => {
+                self.first.start_round();
+                self.starting = true;
+            }
+        }
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.standings = standings.to_vec();
+        self.current().round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        self.current().report(outcome);
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.current().report_fault(fault);
+    }
+
+    fn report_failure(&mut self, player1: PlayerId, player2: PlayerId) {
+        self.current().report_failure(player1, player2);
+    }
+
+    fn add_player(&mut self, player: PlayerId) {
+        self.current().add_player(player);
+    }
+
+    fn remove_player(&mut self, player: PlayerId) {
+        self.removed.push(player);
+        self.current().remove_player(player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        if self.second.is_none() {
+            match self.first.get() {
+                Some(pair) => {
+                    self.starting = false;
+                    return Some(pair);
+                }
+                None if !self.starting => return None,
+                None => self.start_second(),
+            };
+        }
+        self.current().get()
+    }
+
+    /// Blocks, i.e. returns `Error::WouldBlock`, while the current stage blocks. A round of the
+    /// first stage that would block on its first pairing is not taken as the end of the stage.
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        if self.second.is_none() {
+            match self.first.try_get()? {
+                Some(pair) => {
+                    self.starting = false;
+                    return Ok(Some(pair));
+                }
+                None if !self.starting => return Ok(None),
+                None => self.start_second(),
+            };
+        }
+        self.current().try_get()
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        match &self.second {
+            Some(second) => second.remaining(),
+            // the size of the second stage is not known yet
+            None => (self.first.remaining().0, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::InlineExecutor;
+    use crate::scheduling::{KnockoutScheduler, RoundRobbinScheduler};
+    use crate::scoring::Standings;
+    use crate::test_util::{repository, Hesitant, Ids};
+    use crate::tournaments::Tournament;
+
+    /// The final standings of the first stage, best first.
+    const STANDINGS: [(PlayerId, f64); 4] = [(2, 3.0), (0, 2.0), (3, 1.0), (1, 0.0)];
+
+    type Playoff = Staged<RoundRobbinScheduler, KnockoutScheduler, NextStage<KnockoutScheduler>>;
+
+    /// A round robin of four players, after which the top two play a final.
+    fn playoff() -> Playoff {
+        let first = RoundRobbinScheduler::new(vec![0, 1, 2, 3]);
+        let mut staged: Playoff = Staged::new(
+            first,
+            Box::new(|standings: &[(PlayerId, f64)]| {
+                KnockoutScheduler::new(standings.iter().take(2).map(|&(p, _)| p).collect())
+            }),
+        );
+        staged.init();
+        staged
+    }
+
+    /// Plays the rounds of the first stage, and returns their pairings. The second stage is
+    /// created once its first pairing is taken.
+    fn first_stage(staged: &mut Playoff) -> Vec<(PlayerId, PlayerId)> {
+        let mut pairs = Vec::new();
+        while staged.first.remaining().0 > 0 {
+            pairs.extend(std::iter::from_fn(|| staged.get()));
+            staged.round_completed(&STANDINGS);
+            staged.start_round();
+        }
+        pairs
+    }
+
+    #[test]
+    fn test_stages() {
+        let mut staged = playoff();
+        assert_eq!(staged.remaining(), (6, None));
+
+        assert_eq!(first_stage(&mut staged).len(), 6);
+        assert_eq!(staged.remaining(), (0, None));
+
+        // the second stage is seeded by the standings of the first
+        assert_eq!(staged.get(), Some((2, 0)));
+        assert_eq!(staged.get(), None);
+        assert!(staged.second.is_some());
+    }
+
+    #[test]
+    fn test_second_stage_after_blocking() {
+        // the first pairing of every round blocks, including the round that ends the first stage
+        let players = repository::<Ids>(4);
+        let first = Hesitant::new(RoundRobbinScheduler::new(players.ids()));
+        let scheduler = Staged::new(first, |standings: &[(PlayerId, f64)]| {
+            KnockoutScheduler::new(standings.iter().take(2).map(|&(p, _)| p).collect())
+        });
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        tournament.init();
+
+        let pairs = tournament
+            .map(|output| {
+                let (outcome, _) = output.unwrap();
+                let (winner, loser) = outcome.players();
+                (winner.id(), loser.id())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 7);
+        assert_eq!(pairs[6], (3, 2));
+    }
+
+    #[test]
+    fn test_retired_players_are_removed_from_the_second_stage() {
+        let mut staged = playoff();
+        staged.remove_player(0);
+        first_stage(&mut staged);
+
+        // the retired player has a bye, so its opponent is the champion
+        assert_eq!(staged.get(), None);
+        staged.start_round();
+        assert_eq!(staged.second.as_ref().unwrap().champion(), Some(2));
+    }
+}
@@ -0,0 +1,315 @@
+/*------------------------------------------------------------------------------------------------*/
+/*-------------------------------------- Swiss Scheduler -----------------------------------------*/
+/*------------------------------------------------------------------------------------------------*/
+
+use crate::error::Error;
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::PlayerId;
+use crate::scheduling::Scheduler;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The number of pairs the search for a pairing without rematches may try, before rematches are
+/// allowed for the round. Keeps the search fast once few pairings without rematches remain.
+const SEARCH_LIMIT: usize = 10_000;
+
+/// A Swiss system of a fixed number of rounds. In every round, players with equal or similar
+/// points are paired against each other, without pairing the same players twice.
+///
+/// Players are seeded in the order they are given, best first, which breaks ties in points.
+/// A win is worth one point, a draw half a point. If the number of players is odd, the lowest
+/// ranked player that has not had a bye yet sits out the round, and receives a point.
+///
+/// If every pairing of a round would contain a rematch, or no pairing without rematches is
+/// found quickly, rematches are allowed for that round.
+/// Sides are balanced, so that every player plays as Player 1 about as often as as Player 2.
+pub struct SwissScheduler {
+    /// The players in the order of their seeds, best first.
+    players: Vec<PlayerId>,
+    points: HashMap<PlayerId, f64>,
+    /// The opponents each player has been paired against.
+    opponents: HashMap<PlayerId, HashSet<PlayerId>>,
+    /// The matches of each player as Player 1, minus the matches as Player 2.
+    sides: HashMap<PlayerId, i64>,
+    byes: HashSet<PlayerId>,
+    rounds: usize,
+    round: usize,
+    pairs: VecDeque<(PlayerId, PlayerId)>,
+}
+
+impl SwissScheduler {
+    pub fn new(players: Vec<PlayerId>, rounds: usize) -> Self {
+        SwissScheduler {
+            points: players.iter().map(|&player| (player, 0.0)).collect(),
+            players,
+            opponents: HashMap::new(),
+            sides: HashMap::new(),
+            byes: HashSet::new(),
+            rounds,
+            round: 0,
+            pairs: VecDeque::new(),
+        }
+    }
+
+    /// The points of every player, best first. Ties are broken by seed.
+    pub fn standings(&self) -> Vec<(PlayerId, f64)> {
+        let mut standings = self
+            .players
+            .iter()
+            .map(|&player| (player, self.points[&player]))
+            .collect::<Vec<_>>();
+        // the sort is stable, so players with equal points remain in seed order
+        standings.sort_by(|(_, s1), (_, s2)| s2.total_cmp(s1));
+        standings
+    }
+
+    /// Pairs the players of the next round.
+    fn pair_round(&mut self) {
+        self.round += 1;
+        let mut ranking = self
+            .standings()
+            .into_iter()
+            .map(|(player, _)| player)
+            .collect::<Vec<_>>();
+
+        if ranking.len() % 2 == 1 {
+            let bye = ranking
+                .iter()
+                .rposition(|player| !self.byes.contains(player))
+                .unwrap_or(ranking.len() - 1);
+            let player = ranking.remove(bye);
+            self.byes.insert(player);
+            *self.points.get_mut(&player).unwrap() += 1.0;
+        }
+
+        // a player that has played everyone else cannot be paired without a rematch
+        let exhausted = ranking.iter().any(|&player| {
+            ranking
+                .iter()
+                .all(|&opponent| opponent == player || self.played(player, opponent))
+        });
+        let pairing = (!exhausted)
+            .then(|| self.pair(&ranking, false, &mut SEARCH_LIMIT.clone()))
+            .flatten()
+            .or_else(|| self.pair(&ranking, true, &mut usize::MAX.clone()))
+            .unwrap_or_default();
+
+        for (a, b) in pairing {
+            // the player that has played less often as Player 1 is Player 1
+            let (p1, p2) = if self.sides(a) <= self.sides(b) {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            *self.sides.entry(p1).or_insert(0) += 1;
+            *self.sides.entry(p2).or_insert(0) -= 1;
+            self.opponents.entry(p1).or_default().insert(p2);
+            self.opponents.entry(p2).or_default().insert(p1);
+            self.pairs.push_back((p1, p2));
+        }
+    }
+
+    fn sides(&self, player: PlayerId) -> i64 {
+        self.sides.get(&player).copied().unwrap_or(0)
+    }
+
+    fn played(&self, p1: PlayerId, p2: PlayerId) -> bool {
+        self.opponents
+            .get(&p1)
+            .is_some_and(|opponents| opponents.contains(&p2))
+    }
+
+    /// Pairs the highest ranked player with the next highest ranked player it may play,
+    /// backtracking if the remaining players cannot be paired. Gives up once it has tried
+    /// `budget` pairs.
+    fn pair(
+        &self,
+        ranking: &[PlayerId],
+        rematches: bool,
+        budget: &mut usize,
+    ) -> Option<Vec<(PlayerId, PlayerId)>> {
+        let Some((&first, rest)) = ranking.split_first() else {
+            return Some(Vec::new());
+        };
+
+        for (i, &opponent) in rest.iter().enumerate() {
+            if !rematches && self.played(first, opponent) {
+                continue;
+            }
+            if *budget == 0 {
+                return None;
+            }
+            *budget -= 1;
+
+            let mut remaining = rest.to_vec();
+            remaining.remove(i);
+            if let Some(mut pairing) = self.pair(&remaining, rematches, budget) {
+                pairing.insert(0, (first, opponent));
+                return Some(pairing);
+            }
+        }
+        None
+    }
+
+    fn award(&mut self, player: PlayerId, points: f64) {
+        if let Some(total) = self.points.get_mut(&player) {
+            *total += points;
+        }
+    }
+}
+
+impl Scheduler for SwissScheduler {
+    fn init(&mut self) {
+        if self.rounds > 0 {
+            self.pair_round();
+        }
+    }
+
+    fn start_round(&mut self) {
+        if self.round < self.rounds {
+            self.pair_round();
+        }
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        match outcome {
+            LabelledOutcome::Win { winner, .. } => self.award(winner.id(), 1.0),
+            LabelledOutcome::Draw(p1, p2) => {
+                self.award(p1.id(), 0.5);
+                self.award(p2.id(), 0.5);
+            }
+        }
+    }
+
+    /// The opponent of the player at fault wins the match.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.award(fault.opponent.id(), 1.0);
+    }
+
+    /// The player is paired from the next round on, with no points.
+    fn add_player(&mut self, player: PlayerId) {
+        if !self.points.contains_key(&player) {
+            self.players.push(player);
+            self.points.insert(player, 0.0);
+        }
+    }
+
+    /// The player is no longer paired. Its pending pairings are dropped.
+    fn remove_player(&mut self, player: PlayerId) {
+        self.players.retain(|&p| p != player);
+        self.points.remove(&player);
+        self.pairs.retain(|&(p1, p2)| p1 != player && p2 != player);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.pairs.pop_front()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        // Swiss scheduler never blocks within a round
+        Ok(self.get())
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        let rounds = self.rounds.saturating_sub(self.round);
+        let upper = rounds
+            .checked_mul(self.players.len() / 2)
+            .and_then(|upper| upper.checked_add(self.pairs.len()));
+        (self.pairs.len(), upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::Standings;
+    use crate::test_util::{by_strength, Strength};
+    use crate::tournaments::Tournament;
+
+    type SwissTournament = Tournament<Strength, SwissScheduler, Standings>;
+
+    /// Plays a Swiss system between players of the given strengths, seeded in reverse.
+    fn play(strengths: &[usize], rounds: usize) -> (Vec<(PlayerId, PlayerId)>, SwissTournament) {
+        let players = by_strength(strengths);
+
+        let mut seeds = players.ids();
+        seeds.reverse();
+
+        let scheduler = SwissScheduler::new(seeds, rounds);
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+        tournament.init();
+
+        let pairs = tournament
+            .by_ref()
+            .map(|output| {
+                let (outcome, _) = output.unwrap();
+                let (p1, p2) = outcome.players();
+                (p1.id(), p2.id())
+            })
+            .collect();
+        (pairs, tournament)
+    }
+
+    #[test]
+    fn test_no_rematches() {
+        let (pairs, _) = play(&[0, 1, 2, 3, 4, 5], 5);
+        assert_eq!(pairs.len(), 15);
+
+        let unique = pairs
+            .iter()
+            .map(|&(p1, p2)| (p1.min(p2), p1.max(p2)))
+            .collect::<HashSet<_>>();
+        assert_eq!(unique.len(), 15);
+    }
+
+    #[test]
+    fn test_byes() {
+        let (pairs, tournament) = play(&[0, 1, 2, 3, 4], 5);
+        assert_eq!(pairs.len(), 10);
+        // every player sits out exactly one round
+        assert_eq!(tournament.scheduler().byes.len(), 5);
+    }
+
+    #[test]
+    fn test_leader() {
+        let (_, tournament) = play(&[3, 0, 5, 1, 4, 2, 7, 6], 3);
+        let standings = tournament.scheduler().standings();
+        // the strongest player wins every match, the second strongest only loses to it
+        assert_eq!(standings[0], (6, 3.0));
+        assert_eq!(standings[1], (7, 2.0));
+    }
+
+    #[test]
+    fn test_more_rounds_than_opponents() {
+        // rematches become unavoidable, which must not slow down the pairing
+        let strengths = (1..=16).collect::<Vec<_>>();
+        let (pairs, tournament) = play(&strengths, 20);
+        assert_eq!(pairs.len(), 160);
+        assert_eq!(tournament.scheduler().standings()[0], (15, 20.0));
+    }
+
+    #[test]
+    fn test_unpairable_without_rematches() {
+        // every player has played the other group, whose sizes are odd, so that some pairing of
+        // the round must be a rematch, although no player has played everyone
+        let mut swiss = SwissScheduler::new((0..32).collect(), 1);
+        for p1 in 0..15 {
+            for p2 in 15..32 {
+                swiss.opponents.entry(p1).or_default().insert(p2);
+                swiss.opponents.entry(p2).or_default().insert(p1);
+            }
+        }
+        swiss.init();
+
+        let pairs = std::iter::from_fn(|| swiss.get()).collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 16);
+        assert!(pairs.iter().any(|&(p1, p2)| (p1 < 15) != (p2 < 15)));
+    }
+
+    #[test]
+    fn test_sides_are_balanced() {
+        let (pairs, tournament) = play(&[0, 1, 2, 3], 3);
+        assert_eq!(pairs.len(), 6);
+        let sides = &tournament.scheduler().sides;
+        assert!(sides.values().all(|side| side.abs() <= 1));
+    }
+}
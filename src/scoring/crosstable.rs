@@ -0,0 +1,98 @@
+use crate::game::LabelledOutcome;
+use crate::player::{PlayerData, PlayerId};
+use ndarray::Array2;
+use std::collections::HashMap;
+
+/// The head-to-head results of every pair of players.
+///
+/// A win is worth 1 point, a draw half a point for each player. Only played matches are
+/// included. Forfeits are not, as they say nothing about the strength of the players, so the
+/// points of a player may be lower than in its `Standings`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Crosstable {
+    players: Vec<PlayerData>,
+    points: Array2<f64>,
+    games: Array2<u32>,
+}
+
+impl Crosstable {
+    /// Builds the crosstable from a sequence of match outcomes.
+    /// Players are ordered by their id.
+    pub fn from_outcomes<'a, I>(outcomes: I) -> Self
+    where
+        I: IntoIterator<Item = &'a LabelledOutcome>,
+    {
+        let outcomes = outcomes.into_iter().collect::<Vec<_>>();
+
+        let mut players = HashMap::new();
+        for outcome in &outcomes {
+            let (p1, p2) = outcome.players();
+            players.entry(p1.id()).or_insert_with(|| p1.clone());
+            players.entry(p2.id()).or_insert_with(|| p2.clone());
+        }
+        let mut players = players.into_values().collect::<Vec<_>>();
+        players.sort_unstable_by_key(|player| player.id());
+
+        let index = players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| (player.id(), i))
+            .collect::<HashMap<_, _>>();
+
+        let n = players.len();
+        let mut points = Array2::zeros((n, n));
+        let mut games = Array2::zeros((n, n));
+
+        for outcome in outcomes {
+            let (p1, p2) = outcome.players();
+            let (i, j) = (index[&p1.id()], index[&p2.id()]);
+            games[[i, j]] += 1;
+            games[[j, i]] += 1;
+
+            match outcome {
+                LabelledOutcome::Win { .. } => points[[i, j]] += 1.0,
+                LabelledOutcome::Draw(..) => {
+                    points[[i, j]] += 0.5;
+                    points[[j, i]] += 0.5;
+                }
+            }
+        }
+
+        Crosstable {
+            players,
+            points,
+            games,
+        }
+    }
+
+    /// Returns all players appearing in the crosstable, ordered by id.
+    pub fn players(&self) -> &[PlayerData] {
+        &self.players
+    }
+
+    /// Returns the position of a player in `players()`.
+    pub fn index_of(&self, id: PlayerId) -> Option<usize> {
+        self.players.iter().position(|player| player.id() == id)
+    }
+
+    /// Points scored by the player at index `i` against the player at index `j`.
+    pub fn points(&self, i: usize, j: usize) -> f64 {
+        self.points[[i, j]]
+    }
+
+    /// Number of games played between the players at index `i` and `j`.
+    pub fn games(&self, i: usize, j: usize) -> u32 {
+        self.games[[i, j]]
+    }
+
+    /// Total points scored by the player at index `i`.
+    pub fn total_points(&self, i: usize) -> f64 {
+        self.points.row(i).sum()
+    }
+
+    /// Total number of games played by the player at index `i`.
+    pub fn total_games(&self, i: usize) -> u32 {
+        self.games.row(i).sum()
+    }
+}
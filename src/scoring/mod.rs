@@ -1,12 +1,43 @@
-use crate::game::{LabelledOutcome, Match};
-use crate::player::PlayerId;
+mod crosstable;
+mod standings;
+
+pub use crosstable::*;
+pub use standings::*;
+
+use crate::game::{LabelledFault, LabelledOutcome, Match};
+use crate::player::{PlayerData, PlayerId};
+use crate::ranking::RankingPolicy;
 use std::collections::HashMap;
 
 pub trait ScoringSystem<M: Match> {
     /// Updates the scores table based on the result of a match.
     fn report(&mut self, outcome: &LabelledOutcome, match_result: &M::MatchResult);
+
+    /// Updates the scores table for a match that was aborted, because a player was at fault.
+    /// By default, faults are not scored.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        let _ = fault;
+    }
+
+    /// Called by the tournament when a player joins.
+    fn add_player(&mut self, player: &PlayerData) {
+        let _ = player;
+    }
+
+    /// Called by the tournament when a player retires. Scores of retired players are usually
+    /// kept, so that they remain in the final ranking.
+    fn remove_player(&mut self, player: &PlayerData) {
+        let _ = player;
+    }
+
+    /// Returns the score of every player, best first. Passed to the scheduler, and reported to
+    /// observers, after every round. Empty by default.
+    fn standings(&self) -> Vec<(PlayerId, f64)> {
+        Vec::new()
+    }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DefaultScoring {
     player_scores: HashMap<PlayerId, i32>,
 }
@@ -17,24 +48,103 @@ impl DefaultScoring {
 
         DefaultScoring { player_scores }
     }
+
+    /// Returns the current score of every player.
+    pub fn scores(&self) -> &HashMap<PlayerId, i32> {
+        &self.player_scores
+    }
 }
 
 impl<M: Match> ScoringSystem<M> for DefaultScoring {
     fn report(&mut self, outcome: &LabelledOutcome, _: &M::MatchResult) {
         match outcome {
             LabelledOutcome::Win { winner, .. } => {
-                self.player_scores
-                    .entry(winner.id())
-                    .and_modify(|score| *score += 1);
+                *self.player_scores.entry(winner.id()).or_insert(0) += 1;
             }
             LabelledOutcome::Draw(p1, p2) => {
-                self.player_scores
-                    .entry(p1.id())
-                    .and_modify(|score| *score += 1);
-                self.player_scores
-                    .entry(p2.id())
-                    .and_modify(|score| *score += 1);
+                *self.player_scores.entry(p1.id()).or_insert(0) += 1;
+                *self.player_scores.entry(p2.id()).or_insert(0) += 1;
             }
         }
     }
+
+    /// New players start with a score of 0.
+    fn add_player(&mut self, player: &PlayerData) {
+        self.player_scores.entry(player.id()).or_insert(0);
+    }
+
+    fn standings(&self) -> Vec<(PlayerId, f64)> {
+        let mut standings = self
+            .player_scores
+            .iter()
+            .map(|(&player, &score)| (player, score as f64))
+            .collect::<Vec<_>>();
+        standings.sort_by(|(p1, s1), (p2, s2)| s2.total_cmp(s1).then(p1.cmp(p2)));
+        standings
+    }
+
+    /// The opponent of the player at fault wins by forfeit.
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        *self.player_scores.entry(fault.opponent.id()).or_insert(0) += 1;
+    }
+}
+
+impl RankingPolicy for DefaultScoring {
+    fn rank_players(&mut self) -> Vec<PlayerId> {
+        let mut players = self.player_scores.iter().collect::<Vec<_>>();
+        players.sort_by(|(p1, s1), (p2, s2)| s2.cmp(s1).then(p1.cmp(p2)));
+        players.into_iter().map(|(player, _)| *player).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{FaultKind, Outcome};
+    use crate::test_util::{repository, Scored, Unplayed};
+
+    #[test]
+    fn test_default_scoring() {
+        let players = repository::<Unplayed>(3);
+        let data = |id| players.data(id).unwrap();
+
+        let mut scoring = DefaultScoring::new(vec![0, 1]);
+        let draw = LabelledOutcome::Draw(data(0), data(1));
+        ScoringSystem::<Unplayed>::report(&mut scoring, &draw, &Scored(Outcome::Draw));
+
+        // the opponent of the player at fault wins, even if it was not registered
+        let fault = LabelledFault {
+            player: data(0),
+            opponent: data(2),
+            kind: FaultKind::Crash,
+        };
+        ScoringSystem::<Unplayed>::report_fault(&mut scoring, &fault);
+        ScoringSystem::<Unplayed>::report_fault(&mut scoring, &fault);
+
+        assert_eq!(scoring.scores()[&0], 1);
+        assert_eq!(scoring.scores()[&1], 1);
+        assert_eq!(scoring.scores()[&2], 2);
+        assert_eq!(scoring.rank_players(), [2, 0, 1]);
+        assert_eq!(
+            ScoringSystem::<Unplayed>::standings(&scoring),
+            [(2, 2.0), (0, 1.0), (1, 1.0)]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_default_scoring() {
+        let players = repository::<Unplayed>(2);
+        let (winner, loser) = (0, 1);
+        let mut scoring = DefaultScoring::new(players.ids());
+        let win = LabelledOutcome::Win {
+            winner: players.data(winner).unwrap(),
+            loser: players.data(loser).unwrap(),
+        };
+        ScoringSystem::<Unplayed>::report(&mut scoring, &win, &Scored(Outcome::WinP1));
+
+        let json = serde_json::to_string(&scoring).unwrap();
+        let restored: DefaultScoring = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.scores(), scoring.scores());
+    }
 }
@@ -0,0 +1,212 @@
+use crate::game::{LabelledFault, LabelledOutcome, Match};
+use crate::player::{PlayerData, PlayerId};
+use crate::ranking::RankingPolicy;
+use crate::scoring::ScoringSystem;
+use std::collections::HashMap;
+
+/// The win/draw/loss record of a single player. Forfeits are counted as wins and losses.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    pub player: PlayerData,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The number of matches the player forfeited, because it was at fault.
+    pub faults: u32,
+}
+
+impl Record {
+    fn new(player: PlayerData) -> Self {
+        Record {
+            player,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            faults: 0,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// A win is worth 1 point, a draw half a point.
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + 0.5 * self.draws as f64
+    }
+
+    /// Percentage of games won, or 0 if no games were played.
+    pub fn win_percentage(&self) -> f64 {
+        if self.games() == 0 {
+            0.0
+        } else {
+            100.0 * self.wins as f64 / self.games() as f64
+        }
+    }
+}
+
+/// A scoring system that keeps the full win/draw/loss record of every player.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Standings {
+    records: HashMap<PlayerId, Record>,
+}
+
+impl Standings {
+    pub fn new() -> Self {
+        Standings {
+            records: HashMap::new(),
+        }
+    }
+
+    /// Builds the standings from a sequence of match outcomes.
+    pub fn from_outcomes<'a, I>(outcomes: I) -> Self
+    where
+        I: IntoIterator<Item = &'a LabelledOutcome>,
+    {
+        let mut standings = Standings::new();
+        for outcome in outcomes {
+            standings.record(outcome);
+        }
+        standings
+    }
+
+    /// Updates the records with the outcome of a match.
+    pub fn record(&mut self, outcome: &LabelledOutcome) {
+        match outcome {
+            LabelledOutcome::Win { winner, loser } => {
+                self.entry(winner).wins += 1;
+                self.entry(loser).losses += 1;
+            }
+            LabelledOutcome::Draw(p1, p2) => {
+                self.entry(p1).draws += 1;
+                self.entry(p2).draws += 1;
+            }
+        }
+    }
+
+    /// Scores an aborted match as a forfeit: a loss for the player at fault, a win for the
+    /// opponent.
+    pub fn record_fault(&mut self, fault: &LabelledFault) {
+        let record = self.entry(&fault.player);
+        record.losses += 1;
+        record.faults += 1;
+        self.entry(&fault.opponent).wins += 1;
+    }
+
+    fn entry(&mut self, player: &PlayerData) -> &mut Record {
+        self.records
+            .entry(player.id())
+            .or_insert_with(|| Record::new(player.clone()))
+    }
+
+    pub fn get(&self, id: PlayerId) -> Option<&Record> {
+        self.records.get(&id)
+    }
+
+    /// Returns all records, sorted by points, then wins, then id.
+    pub fn records(&self) -> Vec<&Record> {
+        let mut records = self.records.values().collect::<Vec<_>>();
+        records.sort_by(|a, b| {
+            b.points()
+                .total_cmp(&a.points())
+                .then(b.wins.cmp(&a.wins))
+                .then(a.player.id().cmp(&b.player.id()))
+        });
+        records
+    }
+}
+
+impl<M: Match> ScoringSystem<M> for Standings {
+    fn report(&mut self, outcome: &LabelledOutcome, _: &M::MatchResult) {
+        self.record(outcome);
+    }
+
+    fn report_fault(&mut self, fault: &LabelledFault) {
+        self.record_fault(fault);
+    }
+
+    /// New players are listed with an empty record.
+    fn add_player(&mut self, player: &PlayerData) {
+        self.entry(player);
+    }
+
+    fn standings(&self) -> Vec<(PlayerId, f64)> {
+        self.records()
+            .into_iter()
+            .map(|record| (record.player.id(), record.points()))
+            .collect()
+    }
+}
+
+impl RankingPolicy for Standings {
+    fn rank_players(&mut self) -> Vec<PlayerId> {
+        self.records()
+            .into_iter()
+            .map(|record| record.player.id())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::FaultKind;
+    use crate::test_util::{players, Unplayed};
+
+    #[test]
+    fn test_records_order() {
+        let players = players(6);
+        let win = |winner: usize, loser: usize| LabelledOutcome::Win {
+            winner: players[winner].clone(),
+            loser: players[loser].clone(),
+        };
+        let draw =
+            |p1: usize, p2: usize| LabelledOutcome::Draw(players[p1].clone(), players[p2].clone());
+
+        let standings =
+            Standings::from_outcomes(&[win(1, 4), win(1, 5), win(0, 4), draw(0, 2), draw(0, 3)]);
+
+        // players 0 and 1 have 2 points, but player 1 has more wins, the rest are tied
+        let order = standings
+            .records()
+            .into_iter()
+            .map(|record| record.player.id())
+            .collect::<Vec<_>>();
+        assert_eq!(order, [1, 0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_record_fault() {
+        let players = players(2);
+        let mut standings = Standings::new();
+        standings.record(&LabelledOutcome::Draw(
+            players[0].clone(),
+            players[1].clone(),
+        ));
+        ScoringSystem::<Unplayed>::report_fault(
+            &mut standings,
+            &LabelledFault {
+                player: players[0].clone(),
+                opponent: players[1].clone(),
+                kind: FaultKind::Timeout,
+            },
+        );
+
+        let at_fault = standings.get(0).unwrap();
+        assert_eq!(
+            (at_fault.draws, at_fault.losses, at_fault.faults),
+            (1, 1, 1)
+        );
+        assert_eq!(at_fault.games(), 2);
+        let opponent = standings.get(1).unwrap();
+        assert_eq!((opponent.wins, opponent.draws, opponent.faults), (1, 1, 0));
+        assert_eq!(opponent.win_percentage(), 50.0);
+
+        assert_eq!(
+            ScoringSystem::<Unplayed>::standings(&standings),
+            [(1, 1.5), (0, 0.5)]
+        );
+    }
+}
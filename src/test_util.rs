@@ -0,0 +1,163 @@
+//! Matches, players and schedulers shared by the unit tests.
+
+use crate::error::Error;
+use crate::game::{LabelledOutcome, Match, MatchResult, Outcome};
+use crate::player::{Player, PlayerData, PlayerGen, PlayerId, PlayerRepository};
+use crate::scheduling::Scheduler;
+
+/// A match result that only consists of its outcome.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Scored(pub(crate) Outcome);
+
+impl MatchResult for Scored {
+    fn outcome(&self) -> Outcome {
+        self.0.clone()
+    }
+}
+
+/// Never played, only used to create players and label outcomes.
+pub(crate) struct Unplayed;
+
+impl Match for Unplayed {
+    type Agent = ();
+    type MatchResult = Scored;
+
+    fn new(_: Player<Self>, _: Player<Self>) -> Self {
+        Unplayed
+    }
+
+    fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+        unreachable!()
+    }
+}
+
+/// The player with the larger id wins.
+pub(crate) struct Ids(PlayerId, PlayerId);
+
+impl Match for Ids {
+    type Agent = ();
+    type MatchResult = Scored;
+
+    fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+        Ids(player1.id(), player2.id())
+    }
+
+    fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+        Ok(Scored(if self.0 > self.1 {
+            Outcome::WinP1
+        } else {
+            Outcome::WinP2
+        }))
+    }
+}
+
+/// The agent is the strength of the player, and the stronger player wins. Between equally
+/// strong players, the seed of the match decides: odd seeds are won by Player 1, even seeds
+/// are draws. Players of strength 0 cannot play each other.
+pub(crate) struct Strength(usize, usize, u64);
+
+impl Match for Strength {
+    type Agent = usize;
+    type MatchResult = Scored;
+
+    fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+        Strength(player1.unpack(), player2.unpack(), 0)
+    }
+
+    fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+        if self.0 == 0 && self.1 == 0 {
+            anyhow::bail!("no contest");
+        }
+        let outcome = match self.0.cmp(&self.1) {
+            std::cmp::Ordering::Greater => Outcome::WinP1,
+            std::cmp::Ordering::Less => Outcome::WinP2,
+            _ if self.2 % 2 == 1 => Outcome::WinP1,
+            _ => Outcome::Draw,
+        };
+        Ok(Scored(outcome))
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.2 = seed;
+    }
+}
+
+/// A repository of `count` players, with ids starting at 0.
+pub(crate) fn repository<M: Match<Agent = ()>>(count: usize) -> PlayerRepository<M> {
+    let mut players = PlayerRepository::new();
+    for _ in 0..count {
+        players.add_player(PlayerGen::new(|| ()));
+    }
+    players
+}
+
+/// A repository of players of the given strengths, with ids starting at 0.
+pub(crate) fn by_strength(strengths: &[usize]) -> PlayerRepository<Strength> {
+    let mut players = PlayerRepository::new();
+    for &strength in strengths {
+        players.add_player(PlayerGen::new(move || strength));
+    }
+    players
+}
+
+/// The data of `count` players, with ids starting at 0.
+pub(crate) fn players(count: usize) -> Vec<PlayerData> {
+    let players = repository::<Unplayed>(count);
+    players
+        .ids()
+        .into_iter()
+        .map(|id| players.data(id).unwrap())
+        .collect()
+}
+
+/// Would block before every answer of the inner scheduler, as if it waited for another thread.
+/// In particular, it blocks on the first pairing of every round.
+pub(crate) struct Hesitant<P> {
+    inner: P,
+    block: bool,
+}
+
+impl<P> Hesitant<P> {
+    pub(crate) fn new(inner: P) -> Self {
+        Hesitant {
+            inner,
+            block: false,
+        }
+    }
+}
+
+impl<P: Scheduler> Scheduler for Hesitant<P> {
+    fn init(&mut self) {
+        self.inner.init();
+    }
+
+    fn start_round(&mut self) {
+        self.inner.start_round();
+    }
+
+    fn round_completed(&mut self, standings: &[(PlayerId, f64)]) {
+        self.inner.round_completed(standings);
+    }
+
+    fn report(&mut self, outcome: &LabelledOutcome) {
+        self.inner.report(outcome);
+    }
+
+    fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+        self.inner.get()
+    }
+
+    fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+        self.block = !self.block;
+        if self.block {
+            Err(Error::WouldBlock)
+        } else {
+            self.inner.try_get()
+        }
+    }
+
+    fn remaining(&self) -> (usize, Option<usize>) {
+        self.inner.remaining()
+    }
+}
@@ -0,0 +1,243 @@
+use crate::game::{LabelledFault, LabelledOutcome};
+use crate::player::{PlayerData, PlayerId};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Notifications emitted by a `Tournament` while it is running.
+#[derive(Debug, Clone)]
+pub enum TournamentEvent {
+    /// Emitted by `Tournament::init`. `expected_matches` is the upper bound reported by the
+    /// scheduler, if it is known.
+    TournamentStarted { expected_matches: Option<usize> },
+    /// Emitted before the first match of every round. Rounds in which no match is played are
+    /// not reported.
+    RoundStarted { round: usize },
+    /// All matches of a round have completed. `standings` are the scores of the scoring system
+    /// at the end of the round, best first.
+    RoundCompleted {
+        round: usize,
+        standings: Vec<(PlayerId, f64)>,
+    },
+    /// A match has been submitted for execution. `id` counts the matches of the tournament.
+    MatchStarted {
+        id: usize,
+        player1: PlayerData,
+        player2: PlayerData,
+    },
+    /// A match has completed. The outcome is `None` if the match could not be played, in which
+    /// case `fault` names the player at fault, if any.
+    MatchFinished {
+        id: usize,
+        player1: PlayerData,
+        player2: PlayerData,
+        outcome: Option<LabelledOutcome>,
+        fault: Option<LabelledFault>,
+        duration: Duration,
+    },
+    /// A player has been added to the running tournament.
+    PlayerAdded { player: PlayerData },
+    /// A player has retired from the tournament.
+    PlayerRetired { player: PlayerData },
+    /// All matches have completed.
+    TournamentFinished { matches: usize, duration: Duration },
+}
+
+/// Receives the events of a tournament. Closures taking a `&TournamentEvent` are observers.
+pub trait Observer {
+    fn on_event(&mut self, event: &TournamentEvent);
+}
+
+impl<F: FnMut(&TournamentEvent)> Observer for F {
+    fn on_event(&mut self, event: &TournamentEvent) {
+        self(event)
+    }
+}
+
+/// Prints the number of completed matches, the throughput and an estimate of the remaining time.
+///
+/// The total number of matches is taken from the `TournamentStarted` event. Progress is
+/// rewritten in place, so the writer is usually a terminal.
+pub struct ProgressReporter<W: Write> {
+    writer: W,
+    total: Option<usize>,
+    completed: usize,
+    failed: usize,
+    start: Instant,
+    /// Returns the current time, which tests replace with a clock of their own.
+    clock: Box<dyn Fn() -> Instant + Send>,
+}
+
+impl ProgressReporter<std::io::Stderr> {
+    /// Creates a progress reporter that prints to stderr.
+    pub fn stderr() -> Self {
+        ProgressReporter::new(std::io::stderr())
+    }
+}
+
+impl<W: Write> ProgressReporter<W> {
+    pub fn new(writer: W) -> Self {
+        ProgressReporter {
+            writer,
+            total: None,
+            completed: 0,
+            failed: 0,
+            start: Instant::now(),
+            clock: Box::new(Instant::now),
+        }
+    }
+
+    /// Completed matches per second since the tournament started.
+    pub fn throughput(&self) -> f64 {
+        let elapsed = (self.clock)()
+            .saturating_duration_since(self.start)
+            .as_secs_f64();
+        if elapsed > 0.0 {
+            self.completed as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated time until all matches are completed, if the total is known.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.total?.saturating_sub(self.completed);
+        let throughput = self.throughput();
+        if throughput > 0.0 {
+            Some(Duration::from_secs_f64(remaining as f64 / throughput))
+        } else {
+            None
+        }
+    }
+
+    fn print(&mut self) {
+        let total = self
+            .total
+            .map_or_else(|| "?".to_string(), |total| total.to_string());
+        let eta = self
+            .eta()
+            .map_or_else(|| "--:--".to_string(), format_duration);
+
+        // progress output is best effort, and must not abort the tournament
+        let _ = write!(
+            self.writer,
+            "\r{}/{} matches ({} failed) | {:.2} matches/s | ETA {}",
+            self.completed,
+            total,
+            self.failed,
+            self.throughput(),
+            eta
+        );
+        let _ = self.writer.flush();
+    }
+}
+
+impl<W: Write> Observer for ProgressReporter<W> {
+    fn on_event(&mut self, event: &TournamentEvent) {
+        match event {
+            TournamentEvent::TournamentStarted { expected_matches } => {
+                self.total = *expected_matches;
+                self.start = (self.clock)();
+                self.print();
+            }
+            TournamentEvent::MatchFinished { outcome, .. } => {
+                self.completed += 1;
+                if outcome.is_none() {
+                    self.failed += 1;
+                }
+                self.print();
+            }
+            TournamentEvent::TournamentFinished { duration, .. } => {
+                let _ = writeln!(
+                    self.writer,
+                    "\rFinished {} matches in {}",
+                    self.completed,
+                    format_duration(*duration)
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Formats a duration as `mm:ss`, or `h:mm:ss` if it is longer than an hour.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::players;
+    use std::sync::{Arc, Mutex};
+
+    /// A drawn match, or a failed match if it was not `played`.
+    fn finished(id: usize, played: bool) -> TournamentEvent {
+        let [player1, player2] = <[PlayerData; 2]>::try_from(players(2)).unwrap();
+
+        TournamentEvent::MatchFinished {
+            id,
+            outcome: played.then(|| LabelledOutcome::Draw(player1.clone(), player2.clone())),
+            player1,
+            player2,
+            fault: None,
+            duration: Duration::from_millis(10),
+        }
+    }
+
+    /// A reporter whose clock is advanced by hand, starting at the returned instant.
+    fn reporter() -> (ProgressReporter<Vec<u8>>, Arc<Mutex<Instant>>) {
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let mut reporter = ProgressReporter::new(Vec::new());
+        let clock = now.clone();
+        reporter.clock = Box::new(move || *clock.lock().unwrap());
+        (reporter, now)
+    }
+
+    #[test]
+    fn test_progress_reporter() {
+        let (mut reporter, now) = reporter();
+        reporter.on_event(&TournamentEvent::TournamentStarted {
+            expected_matches: Some(6),
+        });
+        assert_eq!(reporter.eta(), None);
+
+        *now.lock().unwrap() += Duration::from_secs(4);
+        reporter.on_event(&finished(0, false));
+        for id in 1..4 {
+            reporter.on_event(&finished(id, true));
+        }
+
+        // 4 matches in 4 seconds, so the 2 remaining take another 2 seconds
+        assert_eq!(reporter.throughput(), 1.0);
+        assert_eq!(reporter.eta(), Some(Duration::from_secs(2)));
+
+        reporter.on_event(&TournamentEvent::TournamentFinished {
+            matches: 4,
+            duration: Duration::from_secs(3725),
+        });
+        let output = String::from_utf8(reporter.writer).unwrap();
+        assert!(output.starts_with("\r0/6 matches (0 failed) | 0.00 matches/s | ETA --:--"));
+        assert!(output.contains("\r4/6 matches (1 failed) | 1.00 matches/s | ETA 00:02"));
+        assert!(output.ends_with("\rFinished 4 matches in 1:02:05\n"));
+    }
+
+    #[test]
+    fn test_unknown_total() {
+        let (mut reporter, now) = reporter();
+        reporter.on_event(&TournamentEvent::TournamentStarted {
+            expected_matches: None,
+        });
+        *now.lock().unwrap() += Duration::from_secs(1);
+        reporter.on_event(&finished(0, false));
+
+        assert_eq!(reporter.eta(), None);
+        let output = String::from_utf8(reporter.writer).unwrap();
+        assert!(output.ends_with("\r1/? matches (1 failed) | 1.00 matches/s | ETA --:--"));
+    }
+}
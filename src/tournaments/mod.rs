@@ -1,27 +1,75 @@
-use crate::game::{LabelledOutcome, Match};
-use crate::player::PlayerRepository;
-use crate::prelude::MatchResult;
+mod events;
+#[cfg(feature = "async")]
+mod stream;
+
+pub use events::*;
+#[cfg(feature = "async")]
+pub use stream::*;
+
+use crate::error::Error;
+use crate::executor::{Completed, Executor, Job, ThreadPoolExecutor};
+use crate::game::{LabelledOutcome, Match, Side};
+use crate::player::{PlayerData, PlayerGen, PlayerId, PlayerRepository};
 use crate::scheduling::Scheduler;
 use crate::scoring::ScoringSystem;
-use anyhow::Error;
-use std::sync::mpsc;
-use threadpool::ThreadPool;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// The result of a single match, as yielded by the tournament.
+pub type MatchOutput<M> = Result<(LabelledOutcome, <M as Match>::MatchResult), Error>;
+
+/// How long the tournament waits before it asks a blocked scheduler again, when no match is
+/// in progress that could unblock it.
+const BLOCKED_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What happens to the pairings of a player that retires from a running tournament.
+/// Matches that are already in progress are played out either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retirement {
+    /// Pairings with the player are dropped, as if they had never been scheduled.
+    Withdraw,
+    /// Pairings with the player are forfeited, and scored as faults of the player.
+    Forfeit,
+}
 
-pub struct Tournament<M, P, S>
+/// Plays the matches of a scheduler, and reports their results to a scoring system.
+///
+/// Matches are played by the executor `E`, which defaults to a local thread pool.
+///
+/// If the scheduler fails with an error other than `Error::WouldBlock`, the error is yielded,
+/// and the tournament finishes once the matches in progress have completed.
+pub struct Tournament<M, P, S, E = ThreadPoolExecutor<M>>
 where
     M: Match,
     P: Scheduler,
     S: ScoringSystem<M>,
+    E: Executor<M>,
 {
     scheduler: P,
     scoring_policy: S,
     player_repository: PlayerRepository<M>,
-    thread_pool: ThreadPool,
+    executor: E,
+    rng: StdRng,
     active_matches: usize,
-    channel: (
-        mpsc::Sender<Result<(LabelledOutcome, M::MatchResult), Error>>,
-        mpsc::Receiver<Result<(LabelledOutcome, M::MatchResult), Error>>,
-    ),
+    observers: Vec<Box<dyn Observer + Send>>,
+    started_matches: usize,
+    /// The current round, starting at 1.
+    round: usize,
+    /// The number of matches started in the current round, including forfeits.
+    round_matches: usize,
+    /// Whether the scheduler has started a new round, whose first pairing is not known yet.
+    starting: bool,
+    /// The first pairing of a round, taken from the scheduler to tell whether the round exists.
+    pending: Option<(PlayerId, PlayerId)>,
+    retired: HashMap<PlayerId, Retirement>,
+    /// Forfeited matches, that are yielded before any other.
+    forfeits: VecDeque<Completed<M>>,
+    start: Instant,
+    /// Whether the scheduler has failed, after which no further matches are started.
+    aborted: bool,
+    finished: bool,
 }
 
 impl<M, P, S> Tournament<M, P, S>
@@ -30,96 +78,798 @@ where
     P: Scheduler,
     S: ScoringSystem<M>,
 {
+    /// Creates a tournament that plays its matches on a single thread of a thread pool.
     pub fn new(scheduler: P, scoring_policy: S, player_repository: PlayerRepository<M>) -> Self {
         Tournament {
             scheduler,
             scoring_policy,
             player_repository,
-            thread_pool: ThreadPool::new(1),
+            executor: ThreadPoolExecutor::new(1),
+            rng: StdRng::from_entropy(),
             active_matches: 0,
-            channel: mpsc::channel::<Result<(LabelledOutcome, M::MatchResult), Error>>(),
+            observers: Vec::new(),
+            started_matches: 0,
+            round: 1,
+            round_matches: 0,
+            starting: false,
+            pending: None,
+            retired: HashMap::new(),
+            forfeits: VecDeque::new(),
+            start: Instant::now(),
+            aborted: false,
+            finished: false,
         }
     }
 
+    /// Sets the number of threads of the thread pool.
     pub fn set_threads(&mut self, count: usize) {
-        self.thread_pool.set_num_threads(count);
+        self.executor.set_threads(count);
+    }
+}
+
+impl<M, P, S, E> Tournament<M, P, S, E>
+where
+    M: Match + 'static,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    /// Replaces the executor that plays the matches.
+    /// Must be called before the tournament is started.
+    pub fn with_executor<X: Executor<M>>(self, executor: X) -> Tournament<M, P, S, X> {
+        Tournament {
+            scheduler: self.scheduler,
+            scoring_policy: self.scoring_policy,
+            player_repository: self.player_repository,
+            executor,
+            rng: self.rng,
+            active_matches: self.active_matches,
+            observers: self.observers,
+            started_matches: self.started_matches,
+            round: self.round,
+            round_matches: self.round_matches,
+            starting: self.starting,
+            pending: self.pending,
+            retired: self.retired,
+            forfeits: self.forfeits,
+            start: self.start,
+            aborted: self.aborted,
+            finished: self.finished,
+        }
+    }
+
+    /// Seeds the generator of the match seeds, to make the tournament reproducible.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Registers an observer, that is notified of all subsequent events.
+    pub fn add_observer<O: Observer + Send + 'static>(&mut self, observer: O) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Returns the scheduler, e.g. to query the champion of a knockout bracket.
+    pub fn scheduler(&self) -> &P {
+        &self.scheduler
+    }
+
+    /// Returns the scoring system, including all results reported so far.
+    pub fn scoring(&self) -> &S {
+        &self.scoring_policy
+    }
+
+    /// Consumes the tournament and returns its scoring system.
+    pub fn into_scoring(self) -> S {
+        self.scoring_policy
+    }
+
+    /// Returns all players of the tournament, including retired players.
+    pub fn players(&self) -> &PlayerRepository<M> {
+        &self.player_repository
+    }
+
+    /// Registers a new player, even while the tournament is running, and returns its id.
+    /// The scheduler decides whether and when the player is paired.
+    pub fn add_player(&mut self, player: PlayerGen<M>) -> PlayerId {
+        let id = self.player_repository.add_player(player);
+        self.joined(id);
+        id
+    }
+
+    /// Like `add_player`, for a player that can later be found by `key`.
+    ///
+    /// Returns an error if another player already uses the key.
+    pub fn add_player_with_key(
+        &mut self,
+        key: impl Into<String>,
+        player: PlayerGen<M>,
+    ) -> Result<PlayerId, Error> {
+        let id = self.player_repository.add_player_with_key(key, player)?;
+        self.joined(id);
+        Ok(id)
+    }
+
+    fn joined(&mut self, id: PlayerId) {
+        self.scheduler.add_player(id);
+        let player = self
+            .player_repository
+            .data(id)
+            .expect("player was just added");
+        self.scoring_policy.add_player(&player);
+        self.emit(TournamentEvent::PlayerAdded { player });
+    }
+
+    /// Retires a player, even while the tournament is running. The scheduler stops pairing the
+    /// player, and pairings it has already made are handled according to `retirement`.
+    ///
+    /// The player stays in the repository, so that its results are kept by the scoring system.
+    ///
+    /// Returns an error if the player does not exist.
+    pub fn retire_player(&mut self, id: PlayerId, retirement: Retirement) -> Result<(), Error> {
+        let player = self.player_repository.data(id)?;
+        self.retired.insert(id, retirement);
+        self.scheduler.remove_player(id);
+        self.scoring_policy.remove_player(&player);
+        self.emit(TournamentEvent::PlayerRetired { player });
+        Ok(())
+    }
+
+    pub fn is_retired(&self, id: PlayerId) -> bool {
+        self.retired.contains_key(&id)
     }
 
     pub fn init(&mut self) {
         // initialise the scheduler
         self.scheduler.init();
+
+        self.start = Instant::now();
+        self.emit(TournamentEvent::TournamentStarted {
+            expected_matches: self.scheduler.remaining().1,
+        });
+    }
+
+    fn emit(&mut self, event: TournamentEvent) {
+        for observer in &mut self.observers {
+            observer.on_event(&event);
+        }
+    }
+
+    /// Takes the next pairing from the scheduler, and prepares its match.
+    /// Pairings with retired players are dropped, or queued as forfeits.
+    ///
+    /// Like `Scheduler::try_get`, returns `Ok(None)` at the end of the round, and an error if
+    /// the scheduler would block.
+    fn next_job(&mut self) -> Result<Option<Job<M>>, Error> {
+        // the round ends once the first pairing of the next round is known
+        if self.starting {
+            return Ok(None);
+        }
+
+        loop {
+            let (p1, p2) = match self.pending.take() {
+                Some(pair) => pair,
+                None => match self.scheduler.try_get()? {
+                    Some(pair) => pair,
+                    None => return Ok(None),
+                },
+            };
+
+            let retired = [(p1, Side::Player1), (p2, Side::Player2)]
+                .into_iter()
+                .find_map(|(player, side)| Some((side, *self.retired.get(&player)?)));
+
+            match retired {
+                None => {}
+                Some((_, Retirement::Withdraw)) => {
+                    // the pairing is not played, which the scheduler may need to know
+                    self.scheduler.report_failure(p1, p2);
+                    continue;
+                }
+                Some((side, Retirement::Forfeit)) => {
+                    self.forfeit(p1, p2, side);
+                    continue;
+                }
+            }
+
+            let id = self.started_matches;
+            let job = Job::new(id, self.rng.gen(), &self.player_repository, p1, p2)
+                .expect("Player not found");
+            self.start_match(id, job.player1().clone(), job.player2().clone());
+            return Ok(Some(job));
+        }
+    }
+
+    /// Counts a match of the current round, which is started with its first match.
+    fn start_match(&mut self, id: usize, player1: PlayerData, player2: PlayerData) {
+        if self.round_matches == 0 {
+            self.emit(TournamentEvent::RoundStarted { round: self.round });
+        }
+        self.started_matches += 1;
+        self.round_matches += 1;
+        self.emit(TournamentEvent::MatchStarted {
+            id,
+            player1,
+            player2,
+        });
+    }
+
+    /// Queues a match that the retired player on `side` forfeits.
+    fn forfeit(&mut self, p1: PlayerId, p2: PlayerId, side: Side) {
+        let id = self.started_matches;
+        let player1 = self.player_repository.data(p1).expect("Player not found");
+        let player2 = self.player_repository.data(p2).expect("Player not found");
+        self.start_match(id, player1.clone(), player2.clone());
+        self.forfeits.push_back(Completed {
+            id,
+            player1,
+            player2,
+            output: Err(Error::at_fault(side, "retired from the tournament")),
+            duration: Duration::ZERO,
+        });
+    }
+
+    /// Reports a completed match to the scoring system and the observers.
+    fn complete(&mut self, completed: Completed<M>) -> MatchOutput<M> {
+        let mut fault = None;
+        match &completed.output {
+            // report the result
+            Ok((outcome, result)) => {
+                self.scoring_policy.report(outcome, result);
+                self.scheduler.report(outcome);
+            }
+            // faults of a player are scored as forfeits, failures of the game are not scored
+            Err(error) => {
+                fault = error
+                    .fault()
+                    .map(|fault| fault.label(completed.player1.clone(), completed.player2.clone()));
+                match &fault {
+                    Some(fault) => {
+                        self.scoring_policy.report_fault(fault);
+                        self.scheduler.report_fault(fault);
+                    }
+                    None => self
+                        .scheduler
+                        .report_failure(completed.player1.id(), completed.player2.id()),
+                }
+            }
+        }
+
+        self.emit(TournamentEvent::MatchFinished {
+            id: completed.id,
+            player1: completed.player1,
+            player2: completed.player2,
+            outcome: completed
+                .output
+                .as_ref()
+                .ok()
+                .map(|(outcome, _)| outcome.clone()),
+            fault,
+            duration: completed.duration,
+        });
+
+        completed.output
+    }
+
+    /// Completes the current round, once all of its matches have completed, and starts the next.
+    /// Returns false if the scheduler has no further rounds, and an error if the scheduler
+    /// would block on the first pairing of the next round. In that case, `next_round` must be
+    /// called again.
+    ///
+    /// A round without any matches is not reported, and its number is taken by the next round.
+    /// The next round is reported with its first match.
+    fn next_round(&mut self) -> Result<bool, Error> {
+        if !self.starting {
+            let standings = self.scoring_policy.standings();
+            self.scheduler.round_completed(&standings);
+            if self.round_matches > 0 {
+                self.emit(TournamentEvent::RoundCompleted {
+                    round: self.round,
+                    standings,
+                });
+            }
+
+            self.scheduler.start_round();
+            self.starting = true;
+        }
+
+        let pair = self.scheduler.try_get()?;
+        self.starting = false;
+        let Some(pair) = pair else {
+            return Ok(false);
+        };
+
+        if self.round_matches > 0 {
+            self.round += 1;
+            self.round_matches = 0;
+        }
+        self.pending = Some(pair);
+        Ok(true)
+    }
+
+    /// Stops starting matches after the scheduler has failed with `error`, which is yielded as
+    /// the output of the tournament. Matches in progress are still played out.
+    fn abort(&mut self, error: Error) -> MatchOutput<M> {
+        self.aborted = true;
+        Err(error)
+    }
+
+    /// Notifies the observers that the tournament has finished, unless they already have been.
+    fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.emit(TournamentEvent::TournamentFinished {
+                matches: self.started_matches,
+                duration: self.start.elapsed(),
+            });
+        }
     }
 }
 
-impl<M, P, S> Iterator for Tournament<M, P, S>
+impl<M, P, S, E> Iterator for Tournament<M, P, S, E>
 where
     M: Match + 'static,
     P: Scheduler,
     S: ScoringSystem<M>,
+    E: Executor<M>,
 {
-    type Item = Result<(LabelledOutcome, M::MatchResult), Error>;
+    type Item = MatchOutput<M>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (tx, results) = &self.channel;
+        if self.finished {
+            return None;
+        }
 
-        while let Ok(pair) = self.scheduler.try_get() {
+        loop {
             // Poll finished matches before queueing more. In a non finite tournament, this will
             // ensure that we don't poll the scheduler forever.
-            if self.active_matches >= self.thread_pool.max_count() {
+            let mut blocked = false;
+            while !self.aborted && self.active_matches < self.executor.capacity() {
+                match self.next_job() {
+                    Ok(Some(job)) => {
+                        self.executor.submit(job);
+                        self.active_matches += 1;
+                    }
+                    Ok(None) => break,
+                    Err(Error::WouldBlock) => {
+                        blocked = true;
+                        break;
+                    }
+                    Err(error) => return Some(self.abort(error)),
+                }
+            }
+
+            if let Some(forfeit) = self.forfeits.pop_front() {
+                return Some(self.complete(forfeit));
+            }
+
+            if self.active_matches > 0 {
                 break;
             }
 
-            match pair {
-                None => {
-                    break;
-                }
-                Some((p1, p2)) => {
-                    let tx = tx.clone();
-
-                    let p1 = self
-                        .player_repository
-                        .get_instance(p1)
-                        .expect("Player not found");
-                    let p2 = self
-                        .player_repository
-                        .get_instance(p2)
-                        .expect("Player not found");
-
-                    let p1_d = p1.data();
-                    let p2_d = p2.data();
-
-                    // Submit the match to be executed
-                    self.thread_pool.execute(move || {
-                        let mut match_ = M::new(p1, p2);
-                        let result = match_.playout();
-
-                        let result_data = result.map(|result| {
-                            (LabelledOutcome::new(result.outcome(), p1_d, p2_d), result)
-                        });
-
-                        tx.send(result_data).expect("Error sending match result")
-                    });
-
-                    self.active_matches += 1;
+            if self.aborted {
+                self.finish();
+                return None;
+            }
+
+            // all matches of the round have completed
+            if !blocked {
+                match self.next_round() {
+                    Ok(true) => continue,
+                    Ok(false) => {
+                        self.finish();
+                        return None;
+                    }
+                    Err(Error::WouldBlock) => {}
+                    Err(error) => return Some(self.abort(error)),
                 }
             }
-        }
 
-        if self.active_matches == 0 {
-            return None;
+            // the scheduler waits for something other than the matches of the tournament
+            std::thread::sleep(BLOCKED_POLL_INTERVAL);
         }
 
         // scheduler is blocking, so we wait on the results
-        let match_result = results.recv().expect("Error receiving match result");
+        let completed = self.executor.recv();
         self.active_matches -= 1;
+        Some(self.complete(completed))
+    }
+}
 
-        if let Ok((ref outcome, ref result)) = match_result {
-            // report the result
-            self.scoring_policy.report(&outcome, &result);
-        } else {
-            // TODO Handle the error
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::InlineExecutor;
+    use crate::game::{FaultKind, LabelledFault};
+    use crate::player::Player;
+    use crate::scheduling::{LadderScheduler, RoundRobbinScheduler, SchedulerExt};
+    use crate::scoring::{DefaultScoring, Standings};
+    use crate::test_util::{repository, Hesitant, Ids, Scored};
+    use std::sync::{Arc, Mutex};
+
+    /// Returns the players of every match.
+    fn played(outputs: impl Iterator<Item = MatchOutput<Ids>>) -> Vec<PlayerId> {
+        outputs
+            .flat_map(|output| {
+                let (outcome, _) = output.unwrap();
+                let (winner, loser) = outcome.players();
+                [winner.id(), loser.id()]
+            })
+            .collect()
+    }
+
+    /// Fails once it has made the given number of pairings.
+    struct Failing<P> {
+        inner: P,
+        pairings: usize,
+    }
+
+    impl<P: Scheduler> Scheduler for Failing<P> {
+        fn init(&mut self) {
+            self.inner.init();
+        }
+
+        fn start_round(&mut self) {
+            self.inner.start_round();
+        }
+
+        fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+            self.inner.get()
+        }
+
+        fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+            if self.pairings == 0 {
+                return Err(Error::Scheduler("out of pairings".to_string()));
+            }
+            let pair = self.inner.try_get()?;
+            if pair.is_some() {
+                self.pairings -= 1;
+            }
+            Ok(pair)
+        }
+    }
+
+    /// Records the rounds and matches of a tournament, e.g. `["R1", "M", "M", "C1"]`.
+    fn record_rounds<P, S, E>(tournament: &mut Tournament<Ids, P, S, E>) -> Arc<Mutex<Vec<String>>>
+    where
+        P: Scheduler,
+        S: ScoringSystem<Ids>,
+        E: Executor<Ids>,
+    {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        tournament.add_observer(move |event: &TournamentEvent| {
+            let event = match event {
+                TournamentEvent::RoundStarted { round } => format!("R{}", round),
+                TournamentEvent::RoundCompleted { round, .. } => format!("C{}", round),
+                TournamentEvent::MatchStarted { .. } => "M".to_string(),
+                _ => return,
+            };
+            recorded.lock().unwrap().push(event);
+        });
+        events
+    }
+
+    #[test]
+    fn test_event_sequence() {
+        let players = repository::<Ids>(3);
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        tournament.add_observer(move |event: &TournamentEvent| {
+            let event = match event {
+                TournamentEvent::TournamentStarted { expected_matches } => {
+                    format!("S{:?}", expected_matches)
+                }
+                TournamentEvent::RoundStarted { round } => format!("R{}", round),
+                TournamentEvent::MatchStarted { id, .. } => format!("M{}", id),
+                TournamentEvent::MatchFinished { id, outcome, .. } => {
+                    assert!(outcome.is_some());
+                    format!("F{}", id)
+                }
+                TournamentEvent::RoundCompleted { round, .. } => format!("C{}", round),
+                TournamentEvent::TournamentFinished { matches, .. } => format!("E{}", matches),
+                _ => return,
+            };
+            recorded.lock().unwrap().push(event);
+        });
+        tournament.init();
+
+        assert_eq!(played(tournament.by_ref()).len(), 6);
+        assert!(tournament.next().is_none());
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                "SSome(3)", "R1", "M0", "F0", "C1", "R2", "M1", "F1", "C2", "R3", "M2", "F2", "C3",
+                "E3"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blocking_scheduler() {
+        let players = repository::<Ids>(4);
+        let scheduler = Hesitant::new(RoundRobbinScheduler::new(players.ids()));
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        let events = record_rounds(&mut tournament);
+        tournament.init();
+
+        assert_eq!(played(tournament).len(), 12);
+        assert_eq!(
+            *events.lock().unwrap(),
+            ["R1", "M", "M", "C1", "R2", "M", "M", "C2", "R3", "M", "M", "C3"]
+        );
+    }
+
+    #[test]
+    fn test_failing_scheduler() {
+        for pairings in [0, 2, 3] {
+            let players = repository::<Ids>(4);
+            let scheduler = Failing {
+                inner: RoundRobbinScheduler::new(players.ids()),
+                pairings,
+            };
+            let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+            tournament.set_threads(2);
+            tournament.init();
+
+            // the matches in progress are played out, and the error is yielded once
+            let outputs = tournament.by_ref().collect::<Vec<_>>();
+            let errors = outputs
+                .iter()
+                .filter(|output| matches!(output, Err(Error::Scheduler(_))))
+                .count();
+            assert_eq!(errors, 1);
+            assert_eq!(
+                outputs.iter().filter(|output| output.is_ok()).count(),
+                pairings
+            );
+            assert!(tournament.next().is_none());
+        }
+    }
+
+    #[test]
+    fn test_every_pairing_is_played_once() {
+        let players = repository::<Ids>(5);
+        let scheduler = Hesitant::new(RoundRobbinScheduler::new(players.ids()));
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+        tournament.set_threads(3);
+        tournament.init();
+
+        // the first pairing of every round is taken from the scheduler before the round starts
+        let mut pairs = played(tournament)
+            .chunks(2)
+            .map(|pair| (pair[0].min(pair[1]), pair[0].max(pair[1])))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        let expected = (0..5)
+            .flat_map(|p1| (p1 + 1..5).map(move |p2| (p1, p2)))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn test_empty_rounds_are_not_reported() {
+        let players = repository::<Ids>(4);
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        let events = record_rounds(&mut tournament);
+        tournament.init();
+
+        // all pairings of the second round are dropped
+        assert_eq!(played(tournament.by_ref().take(2)).len(), 4);
+        for player in 0..4 {
+            tournament
+                .retire_player(player, Retirement::Withdraw)
+                .unwrap();
+        }
+        assert!(tournament.next().is_none());
+        assert_eq!(*events.lock().unwrap(), ["R1", "M", "M", "C1"]);
+    }
+
+    /// Player 0 makes an illegal move, and all other matches fail without a player at fault.
+    struct Faulty(PlayerId, PlayerId);
+
+    impl Match for Faulty {
+        type Agent = ();
+        type MatchResult = Scored;
+
+        fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+            Faulty(player1.id(), player2.id())
         }
 
-        Some(match_result)
+        fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+            match (self.0, self.1) {
+                (0, _) => Err(Error::illegal_move(Side::Player1, "e9 is not a square").into()),
+                (_, 0) => Err(Error::illegal_move(Side::Player2, "e9 is not a square").into()),
+                _ => Err(anyhow::anyhow!("the server is down")),
+            }
+        }
+    }
+
+    /// Records the faults and failures reported to the scheduler.
+    struct Reported<P> {
+        inner: P,
+        reports: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl<P: Scheduler> Scheduler for Reported<P> {
+        fn init(&mut self) {
+            self.inner.init();
+        }
+
+        fn start_round(&mut self) {
+            self.inner.start_round();
+        }
+
+        fn report_fault(&mut self, _: &LabelledFault) {
+            self.reports.lock().unwrap().push("fault");
+        }
+
+        fn report_failure(&mut self, _: PlayerId, _: PlayerId) {
+            self.reports.lock().unwrap().push("failure");
+        }
+
+        fn get(&mut self) -> Option<(PlayerId, PlayerId)> {
+            self.inner.get()
+        }
+
+        fn try_get(&mut self) -> Result<Option<(PlayerId, PlayerId)>, Error> {
+            self.inner.try_get()
+        }
+    }
+
+    #[test]
+    fn test_faults_are_forfeits() {
+        let mut players = PlayerRepository::<Faulty>::new();
+        for _ in 0..3 {
+            players.add_player(PlayerGen::new(|| ()));
+        }
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let scheduler = Reported {
+            inner: RoundRobbinScheduler::new(players.ids()),
+            reports: reports.clone(),
+        };
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+
+        let faults = Arc::new(Mutex::new(Vec::new()));
+        let observed = faults.clone();
+        tournament.add_observer(move |event: &TournamentEvent| {
+            if let TournamentEvent::MatchFinished {
+                fault: Some(fault), ..
+            } = event
+            {
+                let fault = (fault.player.id(), fault.opponent.id(), fault.kind);
+                observed.lock().unwrap().push(fault);
+            }
+        });
+        tournament.init();
+
+        let errors = tournament
+            .by_ref()
+            .map(|output| output.unwrap_err())
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors.iter().filter(|e| e.fault().is_some()).count(), 2);
+
+        let mut faults = faults.lock().unwrap().clone();
+        faults.sort_unstable_by_key(|&(_, opponent, _)| opponent);
+        assert_eq!(
+            faults,
+            [
+                (0, 1, FaultKind::IllegalMove),
+                (0, 2, FaultKind::IllegalMove)
+            ]
+        );
+
+        // the failed match between players 1 and 2 is not scored
+        let scoring = tournament.scoring();
+        let record = scoring.get(0).unwrap();
+        assert_eq!((record.losses, record.faults), (2, 2));
+        for player in [1, 2] {
+            let record = scoring.get(player).unwrap();
+            assert_eq!((record.wins, record.games()), (1, 1));
+        }
+
+        let mut reports = reports.lock().unwrap().clone();
+        reports.sort_unstable();
+        assert_eq!(reports, ["failure", "fault", "fault"]);
+    }
+
+    #[test]
+    fn test_retired_player_forfeits() {
+        let players = repository::<Ids>(4);
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        tournament.init();
+
+        assert!(tournament.next().unwrap().is_ok());
+        tournament.retire_player(3, Retirement::Forfeit).unwrap();
+        assert!(tournament.is_retired(3));
+
+        let outputs = tournament.by_ref().collect::<Vec<_>>();
+        assert_eq!(outputs.len(), 5);
+
+        let forfeits = outputs.iter().filter(|output| output.is_err()).count();
+        // the first match is between players 0 and 1, so all matches of player 3 are forfeited
+        assert_eq!(forfeits, 3);
+        let record = tournament
+            .scoring()
+            .records()
+            .into_iter()
+            .find(|record| record.player.id() == 3)
+            .unwrap();
+        assert_eq!(record.faults as usize, forfeits);
+        assert_eq!(record.wins + record.losses, 3);
+    }
+
+    #[test]
+    fn test_withdrawn_pairings_are_reported() {
+        let players = repository::<Ids>(4);
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let scheduler = Reported {
+            inner: RoundRobbinScheduler::new(players.ids()),
+            reports: reports.clone(),
+        };
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        tournament.init();
+
+        assert!(tournament.next().unwrap().is_ok());
+        tournament.retire_player(3, Retirement::Withdraw).unwrap();
+        assert_eq!(tournament.by_ref().count(), 2);
+
+        // the first match is between players 0 and 1, so all matches of player 3 are dropped
+        assert_eq!(*reports.lock().unwrap(), ["failure"; 3]);
+    }
+
+    #[test]
+    fn test_joined_player_is_scored() {
+        let players = repository::<Ids>(3);
+        let scheduler = LadderScheduler::new(players.ids()).with_seed(2).limit(30);
+        let scoring = DefaultScoring::new(players.ids());
+        let mut tournament =
+            Tournament::new(scheduler, scoring, players).with_executor(InlineExecutor::new());
+        tournament.init();
+
+        played(tournament.by_ref().take(10));
+        let new = tournament.add_player(PlayerGen::new(|| ()));
+        assert_eq!(tournament.scoring().scores().get(&new), Some(&0));
+
+        // the new player has the largest id, and wins all of its matches
+        let games = played(tournament.by_ref())
+            .into_iter()
+            .filter(|&player| player == new)
+            .count();
+        assert!(games > 0);
+        assert_eq!(tournament.scoring().scores()[&new], games as i32);
+    }
+
+    #[test]
+    fn test_players_join_and_withdraw() {
+        let players = repository::<Ids>(3);
+        let scheduler = LadderScheduler::new(players.ids()).with_seed(1).limit(60);
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players)
+            .with_executor(InlineExecutor::new());
+        tournament.init();
+
+        played(tournament.by_ref().take(20));
+        let new = tournament.add_player(PlayerGen::new(|| ()));
+        assert!(played(tournament.by_ref().take(20)).contains(&new));
+
+        tournament.retire_player(0, Retirement::Withdraw).unwrap();
+        let rest = played(tournament.by_ref().take(20));
+        assert_eq!(rest.len(), 40);
+        assert!(!rest.contains(&0));
+
+        // the retired player keeps its results
+        assert_eq!(tournament.scoring().records().len(), 4);
+        assert!(tournament.players().contains(0));
     }
 }
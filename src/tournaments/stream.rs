@@ -0,0 +1,343 @@
+use super::{MatchOutput, Tournament};
+use crate::error::Error;
+use crate::executor::{Completed, Executor};
+use crate::game::{AsyncMatch, Match};
+use crate::scheduling::Scheduler;
+use crate::scoring::ScoringSystem;
+use futures::channel::mpsc;
+use futures::future::BoxFuture;
+use futures::sink::SinkExt;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+
+impl<M, P, S, E> Tournament<M, P, S, E>
+where
+    M: Match + 'static,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    /// Plays the matches of an `AsyncMatch` on the task that polls the returned stream,
+    /// with at most `concurrency` matches in progress at once.
+    ///
+    /// The executor of the tournament is not used.
+    pub fn into_async(self, concurrency: usize) -> AsyncTournament<M, P, S, E>
+    where
+        M: AsyncMatch,
+    {
+        AsyncTournament {
+            tournament: self,
+            running: FuturesUnordered::new(),
+            concurrency: concurrency.max(1),
+        }
+    }
+
+    /// Runs the tournament on a separate thread, and yields its results as a stream.
+    ///
+    /// Matches are played by the executor as usual, so blocking matches do not block the
+    /// async runtime. The thread pauses if more than `buffer` results have not been consumed.
+    pub fn into_stream(self, buffer: usize) -> TournamentStream<M, P, S, E>
+    where
+        Self: Send + 'static,
+    {
+        let (mut tx, results) = mpsc::channel(buffer);
+        let handle = thread::spawn(move || {
+            let mut tournament = self;
+            for output in tournament.by_ref() {
+                // stop early if the stream has been dropped
+                if futures::executor::block_on(tx.send(output)).is_err() {
+                    break;
+                }
+            }
+            tournament
+        });
+
+        TournamentStream { results, handle }
+    }
+}
+
+/// The results of a tournament that runs on a separate thread. See `Tournament::into_stream`.
+pub struct TournamentStream<M, P, S, E>
+where
+    M: Match,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    results: mpsc::Receiver<MatchOutput<M>>,
+    handle: thread::JoinHandle<Tournament<M, P, S, E>>,
+}
+
+impl<M, P, S, E> TournamentStream<M, P, S, E>
+where
+    M: Match,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    /// Returns the tournament, e.g. to access its scoring system.
+    ///
+    /// Blocks until the tournament has finished, which it has once the stream is exhausted.
+    /// If the stream is dropped early, the tournament stops after its current match.
+    pub fn into_tournament(self) -> Tournament<M, P, S, E> {
+        drop(self.results);
+        self.handle.join().expect("Tournament thread panicked")
+    }
+}
+
+impl<M, P, S, E> Stream for TournamentStream<M, P, S, E>
+where
+    M: Match,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    type Item = MatchOutput<M>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.results.poll_next_unpin(cx)
+    }
+}
+
+/// A tournament of an `AsyncMatch`, which yields the results of its matches as a stream.
+/// See `Tournament::into_async`.
+pub struct AsyncTournament<M, P, S, E>
+where
+    M: AsyncMatch,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    tournament: Tournament<M, P, S, E>,
+    running: FuturesUnordered<BoxFuture<'static, Completed<M>>>,
+    concurrency: usize,
+}
+
+impl<M, P, S, E> AsyncTournament<M, P, S, E>
+where
+    M: AsyncMatch + 'static,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    /// Returns the scoring system, including all results reported so far.
+    pub fn scoring(&self) -> &S {
+        self.tournament.scoring()
+    }
+
+    /// Returns the tournament. Matches that are still in progress are cancelled.
+    pub fn into_tournament(self) -> Tournament<M, P, S, E> {
+        self.tournament
+    }
+}
+
+// The tournament is never pinned, only the futures of the running matches, which are boxed.
+impl<M, P, S, E> Unpin for AsyncTournament<M, P, S, E>
+where
+    M: AsyncMatch,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+}
+
+impl<M, P, S, E> Stream for AsyncTournament<M, P, S, E>
+where
+    M: AsyncMatch + 'static,
+    P: Scheduler,
+    S: ScoringSystem<M>,
+    E: Executor<M>,
+{
+    type Item = MatchOutput<M>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.tournament.finished {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let mut blocked = false;
+            while !this.tournament.aborted && this.running.len() < this.concurrency {
+                match this.tournament.next_job() {
+                    Ok(Some(job)) => this.running.push(Box::pin(job.run_async())),
+                    Ok(None) => break,
+                    Err(Error::WouldBlock) => {
+                        blocked = true;
+                        break;
+                    }
+                    Err(error) => return Poll::Ready(Some(this.tournament.abort(error))),
+                }
+            }
+
+            if let Some(forfeit) = this.tournament.forfeits.pop_front() {
+                return Poll::Ready(Some(this.tournament.complete(forfeit)));
+            }
+
+            if !this.running.is_empty() {
+                break;
+            }
+
+            if this.tournament.aborted {
+                this.tournament.finish();
+                return Poll::Ready(None);
+            }
+
+            // all matches of the round have completed
+            if !blocked {
+                match this.tournament.next_round() {
+                    Ok(true) => continue,
+                    Ok(false) => {
+                        this.tournament.finish();
+                        return Poll::Ready(None);
+                    }
+                    Err(Error::WouldBlock) => {}
+                    Err(error) => return Poll::Ready(Some(this.tournament.abort(error))),
+                }
+            }
+
+            // the scheduler waits for something other than the matches of the tournament, so
+            // it is asked again once the runtime has polled its other tasks
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        match this.running.poll_next_unpin(cx) {
+            Poll::Ready(Some(completed)) => Poll::Ready(Some(this.tournament.complete(completed))),
+            Poll::Ready(None) => unreachable!("running matches are not empty"),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{MatchResult, Outcome};
+    use crate::player::{Player, PlayerGen, PlayerRepository};
+    use crate::scheduling::RoundRobbinScheduler;
+    use crate::scoring::Standings;
+    use std::future::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Counts the matches in progress, shared by all agents of a tournament.
+    #[derive(Default)]
+    struct Running {
+        now: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    /// The player with the larger id wins, after yielding to the executor a few times.
+    struct Slow(usize, usize, Arc<Running>);
+
+    #[derive(Debug)]
+    struct SlowResult(Outcome);
+
+    impl MatchResult for SlowResult {
+        fn outcome(&self) -> Outcome {
+            self.0.clone()
+        }
+    }
+
+    impl Match for Slow {
+        type Agent = Arc<Running>;
+        type MatchResult = SlowResult;
+
+        fn new(player1: Player<Self>, player2: Player<Self>) -> Self {
+            Slow(player1.id(), player2.id(), player1.unpack())
+        }
+
+        fn playout(&mut self) -> Result<Self::MatchResult, anyhow::Error> {
+            futures::executor::block_on(self.playout_async())
+        }
+    }
+
+    impl AsyncMatch for Slow {
+        fn playout_async(
+            &mut self,
+        ) -> impl Future<Output = Result<SlowResult, anyhow::Error>> + Send {
+            let outcome = if self.0 > self.1 {
+                Outcome::WinP1
+            } else {
+                Outcome::WinP2
+            };
+
+            let running = self.2.clone();
+
+            async move {
+                let now = running.now.fetch_add(1, Ordering::SeqCst) + 1;
+                running.max.fetch_max(now, Ordering::SeqCst);
+                for _ in 0..3 {
+                    yield_now().await;
+                }
+                running.now.fetch_sub(1, Ordering::SeqCst);
+                Ok(SlowResult(outcome))
+            }
+        }
+    }
+
+    /// Returns pending once, after waking the task again.
+    fn yield_now() -> impl Future<Output = ()> {
+        let mut yielded = false;
+        futures::future::poll_fn(move |cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    }
+
+    fn tournament(running: &Arc<Running>) -> Tournament<Slow, RoundRobbinScheduler, Standings> {
+        let mut players = PlayerRepository::new();
+        for _ in 0..6 {
+            let running = running.clone();
+            players.add_player(PlayerGen::new(move || running.clone()));
+        }
+        let scheduler = RoundRobbinScheduler::new(players.ids());
+        let mut tournament = Tournament::new(scheduler, Standings::new(), players);
+        tournament.init();
+        tournament
+    }
+
+    #[test]
+    fn test_async_tournament() {
+        let running = Arc::new(Running::default());
+        let finished = Arc::new(AtomicUsize::new(0));
+        let mut tournament = tournament(&running);
+        let counter = finished.clone();
+        tournament.add_observer(move |event: &crate::tournaments::TournamentEvent| {
+            if let crate::tournaments::TournamentEvent::TournamentFinished { .. } = event {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let mut stream = tournament.into_async(3);
+        let results = futures::executor::block_on(stream.by_ref().collect::<Vec<_>>());
+
+        assert_eq!(results.len(), 15);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(running.max.load(Ordering::SeqCst), 3);
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+
+        let records = stream.scoring().records();
+        assert_eq!(records[0].player.id(), 5);
+        assert_eq!(records[0].wins, 5);
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut stream = tournament(&Arc::default()).into_stream(1);
+        let results = futures::executor::block_on(stream.by_ref().collect::<Vec<_>>());
+        assert_eq!(results.len(), 15);
+
+        let tournament = stream.into_tournament();
+        assert_eq!(tournament.scoring().records()[0].player.id(), 5);
+    }
+}
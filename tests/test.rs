@@ -9,7 +9,9 @@ use glasswing::games::counting_game::{CountingGame, CountingGameEvaluator};
 use rand::rngs::{StdRng, ThreadRng};
 use rand::{thread_rng, SeedableRng};
 use std::time::Duration;
+use tournament_rs::export::Leaderboard;
 use tournament_rs::game::LabelledOutcome;
+use tournament_rs::history::MatchHistory;
 use tournament_rs::player::{PlayerGen, PlayerRepository};
 use tournament_rs::prelude::{MatchResult, Tournament};
 use tournament_rs::scheduling::RoundRobbinScheduler;
@@ -40,19 +42,25 @@ fn hello_world() {
     );
 
     tournament.init();
+    let mut history = MatchHistory::new();
     for x in tournament {
         match x {
-            Ok((outcome, _)) => match outcome {
-                LabelledOutcome::Win { winner, loser } => {
-                    println!("{:?} beat {:?}", winner, loser);
+            Ok((outcome, result)) => {
+                match &outcome {
+                    LabelledOutcome::Win { winner, loser } => {
+                        println!("{} beat {}", winner.name(), loser.name());
+                    }
+                    LabelledOutcome::Draw(p1, p2) => {
+                        println!("{} drew with {}", p1.name(), p2.name());
+                    }
                 }
-                LabelledOutcome::Draw(p1, p2) => {
-                    println!("{:?} drew with {:?}", p1, p2);
-                }
-            },
+                history.push(outcome, result);
+            }
             Err(e) => {
                 println!("{}", e);
             }
         }
     }
+
+    println!("{}", Leaderboard::from_outcomes(history.outcomes()).unwrap());
 }